@@ -0,0 +1,238 @@
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::pleroma::{account::Account, html::strip_html};
+
+use super::emoji;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AccountListKind {
+    FavouritedBy,
+    RebloggedBy,
+}
+
+impl AccountListKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            AccountListKind::FavouritedBy => "Favourited by",
+            AccountListKind::RebloggedBy => "Boosted by",
+        }
+    }
+}
+
+/// The accounts that interacted with a post
+#[derive(Clone)]
+pub struct AccountList {
+    pub kind: AccountListKind,
+    pub tweet_id: String,
+    /// `None` until the server answers
+    pub accounts: Option<Vec<Account>>,
+    /// Cursor of the next page, `None` once all are loaded
+    pub next: Option<String>,
+    pub i: usize,
+}
+
+impl AccountList {
+    pub fn new(kind: AccountListKind, tweet_id: String) -> Self {
+        AccountList {
+            kind,
+            tweet_id,
+            accounts: None,
+            next: None,
+            i: 0,
+        }
+    }
+
+    pub fn selected(&self) -> Option<&Account> {
+        self.accounts.as_ref()?.get(self.i)
+    }
+
+    /// Returns the cursor of the next page when moving past the last account
+    pub fn down(&mut self) -> Option<String> {
+        let len = self.accounts.as_ref().map(|a| a.len()).unwrap_or(0);
+        if self.i + 1 < len {
+            self.i += 1;
+            None
+        } else {
+            self.next.take()
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.i = self.i.saturating_sub(1);
+    }
+}
+
+pub struct AccountListWidget<'a> {
+    list: &'a AccountList,
+}
+
+impl<'a> From<&'a AccountList> for AccountListWidget<'a> {
+    fn from(value: &'a AccountList) -> Self {
+        AccountListWidget { list: value }
+    }
+}
+
+impl Widget for AccountListWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        buf.set_stringn(
+            area.left(),
+            area.top(),
+            format!("{} | Profile (Enter) | Back (Esc)", self.list.kind.title()),
+            area.width as usize,
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+
+        let accounts = match &self.list.accounts {
+            Some(accounts) => accounts,
+            None => {
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1,
+                    "Loading...",
+                    area.width as usize,
+                    Style::default().fg(Color::DarkGray),
+                );
+                return;
+            }
+        };
+
+        let rows = area.height.saturating_sub(1) as usize;
+        let skip = (self.list.i + 1).saturating_sub(rows);
+        accounts
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(rows)
+            .for_each(|(i, account)| {
+                let style = if i == self.list.i {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                let mut spans = emoji::spans(
+                    &account.display_name,
+                    &account.emojis,
+                    style.add_modifier(Modifier::BOLD),
+                );
+                spans.push(
+                    Span::default()
+                        .content(format!(" @{}", account.acct))
+                        .style(style.fg(Color::DarkGray)),
+                );
+                buf.set_line(
+                    area.left(),
+                    area.top() + 1 + (i - skip) as u16,
+                    &Line::default().spans(spans),
+                    area.width,
+                );
+            });
+    }
+}
+
+pub struct ProfileWidget<'a> {
+    account: &'a Account,
+}
+
+impl<'a> From<&'a Account> for ProfileWidget<'a> {
+    fn from(value: &'a Account) -> Self {
+        ProfileWidget { account: value }
+    }
+}
+
+impl Widget for ProfileWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let account = self.account;
+        let mut name = emoji::spans(
+            &account.display_name,
+            &account.emojis,
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+        name.extend([
+            Span::default()
+                .content(format!(" @{}", account.acct))
+                .style(Style::default().fg(Color::DarkGray)),
+            Span::default().content(if account.bot { " [bot]" } else { "" }),
+            Span::default()
+                .content(if account.is_admin() {
+                    " [admin]"
+                } else if account.is_moderator() {
+                    " [mod]"
+                } else {
+                    ""
+                })
+                .style(Style::default().fg(Color::Magenta)),
+        ]);
+        let mut lines = vec![
+            Line::default().spans(name),
+            Line::from(format!(
+                "{} posts | {} following | {} followers",
+                account.statuses_count, account.following_count, account.followers_count
+            )),
+            Line::from(
+                Span::default()
+                    .content(account.url.clone())
+                    .style(Style::default().fg(Color::Blue)),
+            ),
+            Line::default(),
+        ];
+        // The bio and the field values are HTML
+        lines.extend(
+            strip_html(&account.note)
+                .trim_end()
+                .lines()
+                .map(|line| Line::from(line.to_string())),
+        );
+        lines.push(Line::default());
+        if let Some(relationship) = account
+            .pleroma
+            .as_ref()
+            .and_then(|p| p.relationship.as_ref())
+        {
+            let states = [
+                (relationship.following, "following"),
+                (relationship.requested, "follow requested"),
+                (relationship.followed_by, "follows you"),
+                (relationship.muting, "muted"),
+                (relationship.blocking, "blocked"),
+            ];
+            let states = states
+                .iter()
+                .filter(|(on, _)| *on)
+                .map(|(_, name)| *name)
+                .collect::<Vec<&str>>();
+            if !states.is_empty() {
+                lines.insert(
+                    2,
+                    Line::from(
+                        Span::default()
+                            .content(states.join(" | "))
+                            .style(Style::default().fg(Color::Cyan)),
+                    ),
+                );
+            }
+        }
+        lines.extend(account.fields.iter().map(|field| {
+            Line::default().spans([
+                Span::default()
+                    .content(format!("{}: ", field.name))
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+                Span::default().content(strip_html(&field.value)),
+            ])
+        }));
+
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+}
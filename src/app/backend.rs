@@ -1,12 +1,52 @@
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
 use anyhow::Result;
-use tokio::sync::mpsc::{Receiver, Sender, channel};
+use cli_log::warn;
+use image::{RgbaImage, load_from_memory};
+use tokio::{
+    sync::{
+        Semaphore,
+        mpsc::{Receiver, Sender, channel},
+    },
+    time::sleep,
+};
+use tokio_tungstenite::tungstenite;
+
+use crate::pleroma::{
+    api::Api,
+    chat::{ChatMessage, NewChatMessage},
+    media::MediaUpload,
+    streaming::StreamEvent,
+    tweet::{NewTweet, Tweet},
+};
 
-use crate::pleroma::api::Api;
+use super::{
+    accounts::AccountListKind,
+    download::{DownloadProgress, file_name},
+    message::Message,
+    preview::{PREVIEW_MAX_SIZE, PreviewCache},
+    thread::Thread,
+};
 
-use super::message::Message;
+/// 64 MiB
+const PREVIEW_CACHE_SIZE: u64 = 64 * 1024 * 1024;
+/// Thumbnails fetched at once
+const PREVIEW_FETCHES: usize = 4;
+/// Keeps progress updates from flooding the app
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+/// First wait before reconnecting to the user stream, doubled each time
+/// it fails again
+const STREAM_RETRY: Duration = Duration::from_secs(5);
+const STREAM_RETRY_MAX: Duration = Duration::from_secs(10 * 60);
 
 pub struct Backend {
     api: Api,
+    preview_cache: Option<Arc<PreviewCache>>,
+    preview_slots: Arc<Semaphore>,
     app_chan: Option<Sender<Message>>,
     recv_end: Receiver<Message>,
     pub send_end: Sender<Message>,
@@ -15,8 +55,14 @@ pub struct Backend {
 impl Backend {
     pub async fn new(api: Api) -> Self {
         let (send_end, recv_end) = channel(10);
+        let preview_cache = PreviewCache::new(PREVIEW_CACHE_SIZE)
+            .inspect_err(|e| warn!("Previews won't be cached: {}", e))
+            .ok()
+            .map(Arc::new);
         Backend {
             api,
+            preview_cache,
+            preview_slots: Arc::new(Semaphore::new(PREVIEW_FETCHES)),
             app_chan: None,
             recv_end,
             send_end,
@@ -24,6 +70,10 @@ impl Backend {
     }
 
     pub async fn start(&mut self) -> Result<()> {
+        tokio::spawn(stream_user(
+            self.api.clone(),
+            self.app_chan.clone().unwrap(),
+        ));
         while !self.app_chan.as_ref().unwrap().is_closed() {
             let message = self.recv_end.recv().await;
             if message.is_none() {
@@ -54,13 +104,483 @@ impl Backend {
                         .send(Message::GetLocalTimelineResponse(res))
                         .await?;
                 }
+                Message::GetListTimeline(id, max_id) => {
+                    let res = self.api.list_timeline(&id, max_id.as_deref()).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetListTimelineResponse(id, res))
+                        .await?;
+                }
+                Message::GetTagTimeline(query, max_id) => {
+                    let res = self.api.tag_timeline(&query, max_id.as_deref()).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetTagTimelineResponse(query, res))
+                        .await?;
+                }
+                Message::GetBookmarks(max_id) => {
+                    let res = self.api.bookmarks(max_id.as_deref()).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetBookmarksResponse(res))
+                        .await?;
+                }
+                Message::GetFavourites(max_id) => {
+                    let res = self.api.favourites(max_id.as_deref()).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetFavouritesResponse(res))
+                        .await?;
+                }
+                Message::GetTweetAccounts(kind, id, max_id) => {
+                    let res = match kind {
+                        AccountListKind::FavouritedBy => {
+                            self.api.favourited_by(&id, max_id.as_deref()).await
+                        }
+                        AccountListKind::RebloggedBy => {
+                            self.api.reblogged_by(&id, max_id.as_deref()).await
+                        }
+                    };
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetTweetAccountsResponse(kind, id, res))
+                        .await?;
+                }
+                Message::BookmarkTweet(id, bookmark) => {
+                    let res = self.api.bookmark_tweet(&id, bookmark).await.map(Box::new);
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::BookmarkTweetResponse(res))
+                        .await?;
+                }
+                Message::GetCustomEmojis => {
+                    let res = self.api.custom_emojis().await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetCustomEmojisResponse(res))
+                        .await?;
+                }
+                Message::ReactTweet(id, emoji, react) => {
+                    let res = self.api.react_tweet(&id, &emoji, react).await.map(Box::new);
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::ReactTweetResponse(res))
+                        .await?;
+                }
+                Message::GetOwnAccount => {
+                    let res = self.api.verify_credentials().await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetOwnAccountResponse(res))
+                        .await?;
+                }
+                Message::DeleteTweet(id, redraft) => {
+                    let res = self.api.delete_tweet(&id).await.map(Box::new);
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::DeleteTweetResponse(res, redraft))
+                        .await?;
+                }
+                Message::GetInstance => {
+                    let res = self.api.instance().await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetInstanceResponse(res))
+                        .await?;
+                }
+                // Uploads and their processing would hold up every other request
+                Message::PostTweet(tweet, attachments) => {
+                    let api = self.api.clone();
+                    let app = self.app_chan.clone().unwrap();
+                    tokio::spawn(async move {
+                        let res = post_tweet(&api, tweet, &attachments).await;
+                        let _ = app.send(Message::PostTweetResponse(res)).await;
+                    });
+                }
+                Message::GetScheduledTweets => {
+                    let res = self.api.scheduled_tweets().await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetScheduledTweetsResponse(res))
+                        .await?;
+                }
+                Message::RescheduleTweet(id, scheduled_at) => {
+                    let res = self.api.reschedule_tweet(&id, &scheduled_at).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::RescheduleTweetResponse(res))
+                        .await?;
+                }
+                Message::CancelScheduledTweet(id) => {
+                    let res = self.api.cancel_scheduled_tweet(&id).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::CancelScheduledTweetResponse(id, res))
+                        .await?;
+                }
+                Message::EditTweet(id, tweet, attachments) => {
+                    let api = self.api.clone();
+                    let app = self.app_chan.clone().unwrap();
+                    tokio::spawn(async move {
+                        let res = edit_tweet(&api, &id, tweet, &attachments)
+                            .await
+                            .map(Box::new);
+                        let _ = app.send(Message::EditTweetResponse(res)).await;
+                    });
+                }
+                Message::GetTweetSource(id) => {
+                    let res = self.api.tweet_source(&id).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetTweetSourceResponse(res))
+                        .await?;
+                }
+                Message::GetTweetHistory(id) => {
+                    let res = self.api.tweet_history(&id).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetTweetHistoryResponse(res))
+                        .await?;
+                }
+                Message::GetConversations(max_id) => {
+                    let res = self.api.conversations(max_id.as_deref()).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetConversationsResponse(res))
+                        .await?;
+                }
+                Message::ReadConversation(id) => {
+                    let res = self.api.read_conversation(&id).await.map(Box::new);
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::ReadConversationResponse(res))
+                        .await?;
+                }
+                Message::RemoveConversation(id) => {
+                    let res = self.api.remove_conversation(&id).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::RemoveConversationResponse(id, res))
+                        .await?;
+                }
+                Message::GetLists => {
+                    let res = self.api.lists().await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetListsResponse(res))
+                        .await?;
+                }
+                Message::CreateList(title) => {
+                    let res = self.api.create_list(&title).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::CreateListResponse(res))
+                        .await?;
+                }
+                Message::RenameList(id, title) => {
+                    let res = self.api.rename_list(&id, &title).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::RenameListResponse(res))
+                        .await?;
+                }
+                Message::DeleteList(id) => {
+                    let res = self.api.delete_list(&id).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::DeleteListResponse(id, res))
+                        .await?;
+                }
+                Message::EditListAccount(id, account_id, add) => {
+                    let res = self.api.edit_list_account(&id, &account_id, add).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::EditListAccountResponse(res, add))
+                        .await?;
+                }
+                Message::GetFollowedTags(max_id) => {
+                    let res = self.api.followed_tags(max_id.as_deref()).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetFollowedTagsResponse(res))
+                        .await?;
+                }
+                Message::GetAllFollowedTags => {
+                    let res = self.api.all_followed_tags().await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetAllFollowedTagsResponse(res))
+                        .await?;
+                }
+                Message::FollowTag(name, follow) => {
+                    let res = self.api.follow_tag(&name, follow).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::FollowTagResponse(res))
+                        .await?;
+                }
+                Message::GetChats => {
+                    let res = self.api.chats().await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetChatsResponse(res))
+                        .await?;
+                }
+                Message::OpenChat(account_id) => {
+                    let res = self.api.chat_by_account(&account_id).await.map(Box::new);
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::OpenChatResponse(res))
+                        .await?;
+                }
+                Message::GetChatMessages(chat_id, max_id) => {
+                    let res = self.api.chat_messages(&chat_id, max_id.as_deref()).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetChatMessagesResponse(chat_id, res))
+                        .await?;
+                }
+                Message::SendToChat(chat_id, content, attachment) => {
+                    let api = self.api.clone();
+                    let app = self.app_chan.clone().unwrap();
+                    tokio::spawn(async move {
+                        let res = send_chat_message(&api, &chat_id, content, attachment.as_ref())
+                            .await
+                            .map(Box::new);
+                        let _ = app.send(Message::SendToChatResponse(chat_id, res)).await;
+                    });
+                }
+                Message::ReadChat(chat_id, last_read_id) => {
+                    let res = self
+                        .api
+                        .read_chat(&chat_id, &last_read_id)
+                        .await
+                        .map(Box::new);
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::ReadChatResponse(res))
+                        .await?;
+                }
+                Message::GetThread(id) => {
+                    let res = self.thread(&id).await;
+                    self.app_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetThreadResponse(res))
+                        .await?;
+                }
+                // Fetched on the side, a page of thumbnails shouldn't hold
+                // up the other requests
+                Message::GetPreviews(urls) => {
+                    for url in urls {
+                        let api = self.api.clone();
+                        let cache = self.preview_cache.clone();
+                        let slots = self.preview_slots.clone();
+                        let app = self.app_chan.clone().unwrap();
+                        tokio::spawn(async move {
+                            let _slot = slots.acquire().await;
+                            let res = preview(&api, cache.as_deref(), &url).await;
+                            let _ = app.send(Message::GetPreviewResponse(url, res)).await;
+                        });
+                    }
+                }
+                // Big files would hold up every other request
+                Message::DownloadMedia(id, thread, directory) => {
+                    let api = self.api.clone();
+                    let app = self.app_chan.clone().unwrap();
+                    tokio::spawn(async move {
+                        let res = download_media(&api, &app, &id, thread, &directory).await;
+                        let _ = app.send(Message::DownloadMediaResponse(res)).await;
+                    });
+                }
                 _ => (),
             }
         }
         Ok(())
     }
 
+    async fn thread(&self, id: &str) -> Result<Thread> {
+        let tweet = self.api.tweet(id).await?;
+        let context = self.api.context(id).await?;
+        Ok(Thread::new(tweet, context))
+    }
+
     pub async fn register_app(&mut self, app: Sender<Message>) {
         self.app_chan = Some(app);
     }
 }
+
+async fn post_tweet(api: &Api, tweet: NewTweet, attachments: &[MediaUpload]) -> Result<()> {
+    let mut media_ids = Vec::new();
+    for attachment in attachments {
+        media_ids.push(api.upload_media(attachment).await?);
+    }
+    api.post_tweet(&tweet.media_ids(media_ids)).await
+}
+
+/// Uploads the new attachments, keeping the ones already in `tweet`
+async fn edit_tweet(
+    api: &Api,
+    id: &str,
+    tweet: NewTweet,
+    attachments: &[MediaUpload],
+) -> Result<Tweet> {
+    let mut media_ids = tweet.kept_media_ids().to_vec();
+    for attachment in attachments {
+        media_ids.push(api.upload_media(attachment).await?);
+    }
+    api.edit_tweet(id, &tweet.media_ids(media_ids)).await
+}
+
+async fn send_chat_message(
+    api: &Api,
+    chat_id: &str,
+    content: String,
+    attachment: Option<&MediaUpload>,
+) -> Result<ChatMessage> {
+    let media_id = match attachment {
+        Some(attachment) => Some(api.upload_media(attachment).await?),
+        None => None,
+    };
+    api.send_chat_message(chat_id, &NewChatMessage { content, media_id })
+        .await
+}
+
+async fn download_media(
+    api: &Api,
+    app: &Sender<Message>,
+    id: &str,
+    thread: bool,
+    directory: &Path,
+) -> Result<usize> {
+    tokio::fs::create_dir_all(directory).await?;
+    let tweet = api.tweet(id).await?;
+    let tweets = if thread {
+        let context = api.context(id).await?;
+        context
+            .ancestors
+            .into_iter()
+            .chain([tweet])
+            .chain(context.descendants)
+            .collect()
+    } else {
+        vec![tweet]
+    };
+
+    // Boosts carry the media in the boosted post
+    let downloads = tweets
+        .iter()
+        .map(|t| t.reblog.as_deref().unwrap_or(t))
+        .flat_map(|t| {
+            t.media_attachments
+                .iter()
+                .map(move |m| (m.url(), directory.join(file_name(t, m))))
+        })
+        .collect::<Vec<_>>();
+
+    let files = downloads.len();
+    for (i, (url, path)) in downloads.iter().enumerate() {
+        let mut last_update = Instant::now();
+        api.download_to(url, path, |downloaded, size| {
+            if last_update.elapsed() < PROGRESS_INTERVAL {
+                return;
+            }
+            last_update = Instant::now();
+            let _ = app.try_send(Message::DownloadProgress(DownloadProgress {
+                file: i + 1,
+                files,
+                downloaded,
+                size,
+            }));
+        })
+        .await?;
+    }
+    Ok(files)
+}
+
+async fn preview(api: &Api, cache: Option<&PreviewCache>, url: &str) -> Result<RgbaImage> {
+    let cached = cache.and_then(|c| c.get(url));
+    let data = match cached {
+        Some(data) => data,
+        None => {
+            let data = api.download(url).await?;
+            // The preview is fine without it
+            if let Some(cache) = cache
+                && let Err(e) = cache.insert(url, &data)
+            {
+                warn!("Couldn't cache preview {}: {}", url, e);
+            }
+            data
+        }
+    };
+    Ok(load_from_memory(&data)?
+        .thumbnail(PREVIEW_MAX_SIZE, PREVIEW_MAX_SIZE)
+        .to_rgba8())
+}
+
+/// Forwards the chat updates of the user stream to the app, reconnecting
+/// until the app is gone
+async fn stream_user(api: Api, app: Sender<Message>) {
+    let mut retry = STREAM_RETRY;
+    while !app.is_closed() {
+        match api.user_stream().await {
+            Ok(mut stream) => {
+                retry = STREAM_RETRY;
+                while let Some(event) = stream.next().await {
+                    let message = match event {
+                        StreamEvent::ChatUpdate(chat) => Message::ChatUpdate(Box::new(chat)),
+                    };
+                    if app.send(message).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            // Not going to show up later
+            Err(e) if is_not_found(&e) => {
+                warn!("The instance has no user stream, chats won't update live");
+                return;
+            }
+            Err(e) => warn!("Couldn't open the user stream: {}", e),
+        }
+        sleep(retry).await;
+        retry = (retry * 2).min(STREAM_RETRY_MAX);
+    }
+}
+
+fn is_not_found(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<tungstenite::Error>(),
+        Some(tungstenite::Error::Http(response)) if response.status().as_u16() == 404
+    )
+}
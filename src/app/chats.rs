@@ -0,0 +1,346 @@
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::pleroma::{
+    chat::{Chat, ChatMessage},
+    html::strip_html,
+    media::MediaUpload,
+};
+
+use super::{
+    emoji,
+    file_picker::{FilePicker, FilePickerWidget},
+    time::absolute,
+};
+
+/// Our Pleroma chats
+#[derive(Clone, Default)]
+pub struct ChatList {
+    /// `None` until the server answers
+    pub chats: Option<Vec<Chat>>,
+    pub i: usize,
+}
+
+impl ChatList {
+    pub fn selected(&self) -> Option<&Chat> {
+        self.chats.as_ref()?.get(self.i)
+    }
+
+    pub fn down(&mut self) {
+        let len = self.chats.as_ref().map(|c| c.len()).unwrap_or(0);
+        if self.i + 1 < len {
+            self.i += 1;
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.i = self.i.saturating_sub(1);
+    }
+
+    /// Moves the updated chat to the top, where the server lists it too
+    pub fn update(&mut self, chat: Chat) {
+        if let Some(chats) = &mut self.chats {
+            chats.retain(|c| c.id != chat.id);
+            chats.insert(0, chat);
+        }
+    }
+}
+
+/// Messages of a chat and the one being written
+#[derive(Clone)]
+pub struct ChatWindow {
+    pub chat: Chat,
+    /// Newest first, `None` until the server answers
+    pub messages: Option<Vec<ChatMessage>>,
+    /// Cursor of older messages, `None` once all are loaded
+    pub next: Option<String>,
+    /// Messages scrolled past, from the newest
+    pub scroll: usize,
+    pub input: String,
+    pub attachment: Option<MediaUpload>,
+    pub picker: Option<FilePicker>,
+    pub sending: bool,
+    pub error: Option<String>,
+}
+
+impl ChatWindow {
+    pub fn new(chat: Chat) -> Self {
+        ChatWindow {
+            chat,
+            messages: None,
+            next: None,
+            scroll: 0,
+            input: String::new(),
+            attachment: None,
+            picker: None,
+            sending: false,
+            error: None,
+        }
+    }
+
+    /// Scrolls towards older messages, returning the cursor of the next
+    /// page once past the oldest loaded
+    pub fn up(&mut self) -> Option<String> {
+        let len = self.messages.as_ref().map(|m| m.len()).unwrap_or(0);
+        if self.scroll + 1 < len {
+            self.scroll += 1;
+            None
+        } else {
+            self.next.take()
+        }
+    }
+
+    pub fn down(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    /// Adds a message that just arrived, false if we already had it
+    pub fn push(&mut self, message: ChatMessage) -> bool {
+        let messages = self.messages.get_or_insert_default();
+        if messages.iter().any(|m| m.id == message.id) {
+            return false;
+        }
+        messages.insert(0, message);
+        true
+    }
+
+    /// Adds a page of older messages, leaving out the ones that already
+    /// arrived from the stream
+    pub fn extend(&mut self, older: Vec<ChatMessage>) {
+        let messages = self.messages.get_or_insert_default();
+        for message in older {
+            if !messages.iter().any(|m| m.id == message.id) {
+                messages.push(message);
+            }
+        }
+    }
+
+    pub fn newest_id(&self) -> Option<&str> {
+        Some(self.messages.as_ref()?.first()?.id.as_str())
+    }
+
+    fn is_mine(&self, message: &ChatMessage) -> bool {
+        message.account_id != self.chat.account.id
+    }
+}
+
+fn display_name(chat: &Chat) -> &str {
+    match chat.account.display_name.as_str() {
+        "" => chat.account.acct.as_str(),
+        name => name,
+    }
+}
+
+fn preview(message: &ChatMessage) -> String {
+    let text = strip_html(&message.content).replace('\n', " ");
+    match &message.attachment {
+        Some(attachment) => format!("{} {}", attachment.icon(), text),
+        None => text,
+    }
+}
+
+pub struct ChatListWidget<'a> {
+    list: &'a ChatList,
+}
+
+impl<'a> From<&'a ChatList> for ChatListWidget<'a> {
+    fn from(value: &'a ChatList) -> Self {
+        ChatListWidget { list: value }
+    }
+}
+
+impl Widget for ChatListWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        buf.set_stringn(
+            area.left(),
+            area.top(),
+            "Chats | Open (Enter) | Back (Esc)",
+            area.width as usize,
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+
+        let chats = match &self.list.chats {
+            Some(chats) => chats,
+            None => {
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1,
+                    "Loading...",
+                    area.width as usize,
+                    Style::default().fg(Color::DarkGray),
+                );
+                return;
+            }
+        };
+
+        let rows = area.height.saturating_sub(1) as usize;
+        let skip = (self.list.i + 1).saturating_sub(rows);
+        chats
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(rows)
+            .for_each(|(i, chat)| {
+                let style = if i == self.list.i {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                let mut spans = vec![Span::default().content(match chat.unread {
+                    0 => "  ".to_string(),
+                    n => format!("{} ", n),
+                })];
+                spans.extend(emoji::spans(
+                    display_name(chat),
+                    &chat.account.emojis,
+                    style.add_modifier(Modifier::BOLD),
+                ));
+                if let Some(message) = &chat.last_message {
+                    spans.push(
+                        Span::default()
+                            .content(format!("  {}", preview(message)))
+                            .style(style.fg(Color::DarkGray)),
+                    );
+                }
+                buf.set_line(
+                    area.left(),
+                    area.top() + 1 + (i - skip) as u16,
+                    &Line::default().spans(spans),
+                    area.width,
+                );
+            });
+    }
+}
+
+pub struct ChatWidget<'a> {
+    window: &'a ChatWindow,
+}
+
+impl<'a> From<&'a ChatWindow> for ChatWidget<'a> {
+    fn from(value: &'a ChatWindow) -> Self {
+        ChatWidget { window: value }
+    }
+}
+
+impl Widget for ChatWidget<'_> {
+    /// The newest messages at the bottom, above the input, the attachment
+    /// and the error line
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let window = self.window;
+        if let Some(picker) = &window.picker {
+            FilePickerWidget::from(picker).render(area, buf);
+            return;
+        }
+
+        let mut title = emoji::spans(
+            display_name(&window.chat),
+            &window.chat.account.emojis,
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+        title.push(Span::default().content(format!(
+            " @{} | Send (Enter) | Attach (^O) | Back (Esc)",
+            window.chat.account.acct
+        )));
+        buf.set_line(
+            area.left(),
+            area.top(),
+            &Line::default().spans(title),
+            area.width,
+        );
+
+        let bottom = area.bottom().saturating_sub(3);
+        let input = if window.sending {
+            "Sending...".to_string()
+        } else {
+            format!("> {}", window.input)
+        };
+        buf.set_stringn(
+            area.left(),
+            bottom,
+            input,
+            area.width as usize,
+            Style::default().fg(Color::Yellow),
+        );
+        if let Some(attachment) = &window.attachment {
+            buf.set_stringn(
+                area.left(),
+                bottom + 1,
+                format!("\u{1f4ce}{} (^X to remove)", attachment.path.display()),
+                area.width as usize,
+                Style::default(),
+            );
+        }
+        if let Some(error) = &window.error {
+            buf.set_stringn(
+                area.left(),
+                bottom + 2,
+                error,
+                area.width as usize,
+                Style::default().fg(Color::Red),
+            );
+        }
+
+        let messages = match &window.messages {
+            Some(messages) => messages,
+            None => {
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1,
+                    "Loading...",
+                    area.width as usize,
+                    Style::default().fg(Color::DarkGray),
+                );
+                return;
+            }
+        };
+
+        // The oldest unread message gets a marker above it
+        let first_unread = messages
+            .iter()
+            .rposition(|m| m.unread && !window.is_mine(m));
+        let mut y = bottom;
+        for (i, message) in messages.iter().enumerate().skip(window.scroll) {
+            if y <= area.top() + 1 {
+                break;
+            }
+            y -= 1;
+            let (name, color) = if window.is_mine(message) {
+                ("you", Color::Cyan)
+            } else {
+                (window.chat.account.acct.as_str(), Color::Green)
+            };
+            let text = preview(message);
+            let mut spans = vec![
+                Span::default()
+                    .content(format!("{} ", absolute(&message.created_at)))
+                    .style(Style::default().fg(Color::DarkGray)),
+                Span::default()
+                    .content(format!("{}: ", name))
+                    .style(Style::default().fg(color)),
+            ];
+            spans.extend(emoji::spans(&text, &message.emojis, Style::default()));
+            buf.set_line(area.left(), y, &Line::default().spans(spans), area.width);
+
+            if Some(i) == first_unread && y > area.top() + 1 {
+                y -= 1;
+                buf.set_stringn(
+                    area.left(),
+                    y,
+                    "\u{2500}\u{2500} new \u{2500}\u{2500}",
+                    area.width as usize,
+                    Style::default().fg(Color::Red),
+                );
+            }
+        }
+    }
+}
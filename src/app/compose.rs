@@ -0,0 +1,515 @@
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::pleroma::{
+    emoji::CustomEmoji,
+    media::MediaUpload,
+    tweet::{ContentType, MediaAttatchment, Tweet, TweetSource, Visibility},
+};
+
+use super::{
+    drafts::{Draft, DraftAttachment},
+    emoji::{completions, is_shortcode},
+    file_picker::{FilePicker, FilePickerWidget},
+    markdown,
+};
+
+#[derive(Clone, Default, PartialEq)]
+pub enum ComposeFocus {
+    #[default]
+    Text,
+    SpoilerText,
+    /// When to publish, empty for now
+    ScheduledAt,
+    /// Attachment of the edited post at the given index
+    Kept(usize),
+    /// Alt text of the attachment at the given index
+    Description(usize),
+}
+
+/// A post being written
+#[derive(Clone, Default)]
+pub struct Compose {
+    pub text: String,
+    /// Content warning
+    pub spoiler_text: String,
+    pub scheduled_at: String,
+    pub in_reply_to_id: Option<String>,
+    /// Id of the post being edited
+    pub editing: Option<String>,
+    /// Attachments of the edited post to keep
+    pub kept_media: Vec<MediaAttatchment>,
+    pub visibility: Visibility,
+    pub content_type: ContentType,
+    /// Show the text as the server will format it
+    pub preview: bool,
+    pub attachments: Vec<MediaUpload>,
+    pub focus: ComposeFocus,
+    pub picker: Option<FilePicker>,
+    pub error: Option<String>,
+    pub sending: bool,
+    /// Draft this is saved as
+    pub draft_id: Option<u64>,
+    /// Changed since last saved as a draft
+    pub dirty: bool,
+}
+
+impl Compose {
+    /// Reopens a deleted post, `tweet` being what the server returned
+    /// when deleting it.
+    pub fn redraft(tweet: &Tweet) -> Self {
+        Compose {
+            // Only Pleroma sends the source
+            text: tweet
+                .text
+                .clone()
+                .unwrap_or_else(|| tweet.plain_content().into_owned()),
+            spoiler_text: tweet.spoiler_text.clone(),
+            in_reply_to_id: tweet.in_reply_to_id.clone(),
+            visibility: tweet.visibility.for_compose(),
+            ..Default::default()
+        }
+    }
+
+    /// Replies to `tweet`, mentioning everyone in it but `me`. Keeps the
+    /// parent's visibility so followers-only threads stay that way.
+    pub fn reply(tweet: &Tweet, me: Option<&str>) -> Self {
+        let mut mentions: Vec<&str> = Vec::new();
+        let accts = [tweet.account.acct.as_str()]
+            .into_iter()
+            .chain(tweet.mentions.iter().map(|m| m.acct.as_str()));
+        for acct in accts {
+            if Some(acct) != me && !mentions.contains(&acct) {
+                mentions.push(acct);
+            }
+        }
+        Compose {
+            text: mentions.iter().map(|acct| format!("@{} ", acct)).collect(),
+            spoiler_text: tweet.spoiler_text.clone(),
+            in_reply_to_id: Some(tweet.id.clone()),
+            visibility: tweet.visibility.for_compose(),
+            ..Default::default()
+        }
+    }
+
+    pub fn from_draft(draft: &Draft) -> Self {
+        Compose {
+            text: draft.text.clone(),
+            spoiler_text: draft.spoiler_text.clone(),
+            in_reply_to_id: draft.in_reply_to_id.clone(),
+            visibility: draft.visibility.for_compose(),
+            content_type: draft.content_type,
+            attachments: draft
+                .attachments
+                .iter()
+                .map(|a| {
+                    let mut media = MediaUpload::new(a.path.clone());
+                    media.description = a.description.clone();
+                    media
+                })
+                .collect(),
+            draft_id: Some(draft.id),
+            ..Default::default()
+        }
+    }
+
+    pub fn to_draft(&self) -> Draft {
+        Draft {
+            id: self.draft_id.unwrap_or_else(Draft::new_id),
+            text: self.text.clone(),
+            spoiler_text: self.spoiler_text.clone(),
+            visibility: self.visibility,
+            content_type: self.content_type,
+            in_reply_to_id: self.in_reply_to_id.clone(),
+            attachments: self
+                .attachments
+                .iter()
+                .map(|a| DraftAttachment {
+                    path: a.path.clone(),
+                    description: a.description.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Nothing worth keeping as a draft
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty()
+            && self.spoiler_text.trim().is_empty()
+            && self.attachments.is_empty()
+    }
+
+    /// Edits one of our posts starting from its source
+    pub fn edit(tweet: &Tweet, source: TweetSource) -> Self {
+        Compose {
+            text: source.text,
+            spoiler_text: source.spoiler_text,
+            in_reply_to_id: tweet.in_reply_to_id.clone(),
+            editing: Some(source.id),
+            kept_media: tweet.media_attachments.clone(),
+            visibility: tweet.visibility.for_compose(),
+            content_type: source
+                .content_type
+                .as_deref()
+                .and_then(ContentType::from_mime)
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    pub fn cycle_visibility(&mut self) {
+        let next = Visibility::COMPOSE
+            .iter()
+            .position(|v| *v == self.visibility)
+            .map(|i| (i + 1) % Visibility::COMPOSE.len())
+            .unwrap_or(0);
+        self.visibility = Visibility::COMPOSE[next];
+    }
+
+    /// Moves to the next of the formats the instance accepts
+    pub fn cycle_content_type(&mut self, formats: &[ContentType]) {
+        let next = formats
+            .iter()
+            .position(|f| *f == self.content_type)
+            .map(|i| (i + 1) % formats.len())
+            .unwrap_or(0);
+        if let Some(format) = formats.get(next) {
+            self.content_type = *format;
+        }
+    }
+
+    /// Text → CW → schedule → first attachment's alt text → ... → Text,
+    /// published posts can't be scheduled
+    pub fn cycle_focus(&mut self) {
+        self.focus = match self.focus {
+            ComposeFocus::Text => ComposeFocus::SpoilerText,
+            ComposeFocus::SpoilerText if self.editing.is_none() => ComposeFocus::ScheduledAt,
+            ComposeFocus::SpoilerText | ComposeFocus::ScheduledAt
+                if !self.kept_media.is_empty() =>
+            {
+                ComposeFocus::Kept(0)
+            }
+            ComposeFocus::Kept(i) if i + 1 < self.kept_media.len() => ComposeFocus::Kept(i + 1),
+            ComposeFocus::SpoilerText | ComposeFocus::ScheduledAt | ComposeFocus::Kept(_)
+                if !self.attachments.is_empty() =>
+            {
+                ComposeFocus::Description(0)
+            }
+            ComposeFocus::Description(i) if i + 1 < self.attachments.len() => {
+                ComposeFocus::Description(i + 1)
+            }
+            _ => ComposeFocus::Text,
+        };
+    }
+
+    /// The field receiving the typed characters, kept attachments can
+    /// only be removed
+    pub fn focused_text(&mut self) -> Option<&mut String> {
+        match self.focus {
+            ComposeFocus::Text => Some(&mut self.text),
+            ComposeFocus::SpoilerText => Some(&mut self.spoiler_text),
+            ComposeFocus::ScheduledAt => Some(&mut self.scheduled_at),
+            ComposeFocus::Kept(_) => None,
+            ComposeFocus::Description(i) => Some(&mut self.attachments[i].description),
+        }
+    }
+
+    /// The shortcode being typed at the end of the focused field, after a
+    /// colon starting a word
+    pub fn emoji_query(&self) -> Option<&str> {
+        let text = match self.focus {
+            ComposeFocus::Text => &self.text,
+            ComposeFocus::SpoilerText => &self.spoiler_text,
+            ComposeFocus::Description(i) => &self.attachments[i].description,
+            ComposeFocus::ScheduledAt | ComposeFocus::Kept(_) => return None,
+        };
+        let colon = text.rfind(':')?;
+        let query = &text[colon + 1..];
+        let starts_word = text[..colon]
+            .chars()
+            .next_back()
+            .is_none_or(char::is_whitespace);
+        (starts_word && query.len() >= 2 && is_shortcode(query)).then_some(query)
+    }
+
+    /// Replaces the shortcode being typed with the complete one
+    pub fn complete_emoji(&mut self, shortcode: &str) {
+        if let Some(len) = self.emoji_query().map(|query| query.len() + 1)
+            && let Some(text) = self.focused_text()
+        {
+            text.truncate(text.len() - len);
+            text.push_str(&format!(":{}: ", shortcode));
+        }
+    }
+
+    pub fn remove_focused_attachment(&mut self) {
+        match self.focus {
+            ComposeFocus::Kept(i) => {
+                self.kept_media.remove(i);
+            }
+            ComposeFocus::Description(i) => {
+                self.attachments.remove(i);
+            }
+            _ => return,
+        }
+        self.focus = ComposeFocus::Text;
+    }
+
+    /// Kept and new ones, for the instance's limit
+    pub fn attachment_count(&self) -> usize {
+        self.kept_media.len() + self.attachments.len()
+    }
+}
+
+pub struct ComposeWidget<'a> {
+    compose: &'a Compose,
+    /// For shortcode completion
+    emojis: &'a [CustomEmoji],
+}
+
+impl<'a> From<&'a Compose> for ComposeWidget<'a> {
+    fn from(value: &'a Compose) -> Self {
+        ComposeWidget {
+            compose: value,
+            emojis: &[],
+        }
+    }
+}
+
+impl<'a> ComposeWidget<'a> {
+    pub fn emojis(mut self, emojis: &'a [CustomEmoji]) -> Self {
+        self.emojis = emojis;
+        self
+    }
+}
+
+impl Widget for ComposeWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        if let Some(picker) = &self.compose.picker {
+            FilePickerWidget::from(picker).render(area, buf);
+            return;
+        }
+
+        let focused = Style::default().fg(Color::Yellow);
+        let header = if self.compose.sending {
+            "Sending...".to_string()
+        } else if self.compose.editing.is_some() {
+            format!(
+                "Editing | Format: {} (^T) | Preview (^P) | Attach (^O) | Save (^S)",
+                self.compose.content_type.name()
+            )
+        } else {
+            format!(
+                "Visibility: {} (^V) | Format: {} (^T) | Preview (^P) | Attach (^O) | Send (^S)",
+                self.compose.visibility.name(),
+                self.compose.content_type.name()
+            )
+        };
+        buf.set_stringn(
+            area.left(),
+            area.top(),
+            header,
+            area.width as usize,
+            Style::default(),
+        );
+
+        let style_for = |focus: ComposeFocus| {
+            if self.compose.focus == focus {
+                focused
+            } else {
+                Style::default()
+            }
+        };
+        let mut spans = vec![
+            Span::default()
+                .content(format!("CW: {}", self.compose.spoiler_text))
+                .style(style_for(ComposeFocus::SpoilerText)),
+        ];
+        if self.compose.editing.is_none() {
+            spans.push(Span::default().content(" | "));
+            spans.push(
+                Span::default()
+                    .content(format!(
+                        "Schedule: {}",
+                        match self.compose.scheduled_at.as_str() {
+                            "" if self.compose.focus != ComposeFocus::ScheduledAt => "now",
+                            at => at,
+                        }
+                    ))
+                    .style(style_for(ComposeFocus::ScheduledAt)),
+            );
+        }
+        if let Some(id) = &self.compose.in_reply_to_id {
+            spans.push(Span::default().content(format!(" | Replying to {}", id)));
+        }
+        buf.set_line(area.left(), area.top() + 1, &Line::from(spans), area.width);
+
+        // Leave room for the header, the CW, the attachments and the error line
+        let text_height = area
+            .height
+            .saturating_sub(3 + self.compose.attachment_count() as u16);
+        if self.compose.preview && self.compose.content_type == ContentType::Markdown {
+            markdown::render(&self.compose.text)
+                .iter()
+                .take(text_height as usize)
+                .enumerate()
+                .for_each(|(i, line)| {
+                    buf.set_line(area.left(), area.top() + 2 + i as u16, line, area.width);
+                });
+        } else {
+            self.compose
+                .text
+                .lines()
+                .take(text_height as usize)
+                .enumerate()
+                .for_each(|(i, line)| {
+                    buf.set_stringn(
+                        area.left(),
+                        area.top() + 2 + i as u16,
+                        line,
+                        area.width as usize,
+                        if self.compose.focus == ComposeFocus::Text {
+                            focused
+                        } else {
+                            Style::default()
+                        },
+                    );
+                });
+        }
+
+        let attachments_top = area.top() + 2 + text_height;
+        self.compose
+            .kept_media
+            .iter()
+            .enumerate()
+            .for_each(|(i, media)| {
+                let name = media.url().rsplit('/').next().unwrap_or_default();
+                buf.set_stringn(
+                    area.left(),
+                    attachments_top + i as u16,
+                    format!(
+                        "{}{} (kept, ^X to remove) alt: {}",
+                        media.icon(),
+                        name,
+                        media.description().unwrap_or_default()
+                    ),
+                    area.width as usize,
+                    if self.compose.focus == ComposeFocus::Kept(i) {
+                        focused
+                    } else {
+                        Style::default()
+                    },
+                );
+            });
+        let attachments_top = attachments_top + self.compose.kept_media.len() as u16;
+        self.compose
+            .attachments
+            .iter()
+            .enumerate()
+            .for_each(|(i, attachment)| {
+                let name = attachment
+                    .path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                buf.set_stringn(
+                    area.left(),
+                    attachments_top + i as u16,
+                    format!("\u{1f4ce}{} alt: {}", name, attachment.description),
+                    area.width as usize,
+                    if self.compose.focus == ComposeFocus::Description(i) {
+                        focused
+                    } else {
+                        Style::default()
+                    },
+                );
+            });
+
+        if let Some(error) = &self.compose.error {
+            buf.set_stringn(
+                area.left(),
+                area.bottom().saturating_sub(1),
+                error,
+                area.width as usize,
+                Style::default().fg(Color::Red),
+            );
+        } else if let Some(query) = self.compose.emoji_query() {
+            let completions = completions(query, self.emojis);
+            if completions.is_empty() {
+                return;
+            }
+            let mut spans = vec![Span::default().content("Complete (Tab): ")];
+            spans.extend(completions.iter().enumerate().map(|(i, emoji)| {
+                Span::default()
+                    .content(format!(":{}: ", emoji.shortcode))
+                    .style(if i == 0 {
+                        Style::default().add_modifier(Modifier::REVERSED)
+                    } else {
+                        Style::default().fg(Color::Magenta)
+                    })
+            }));
+            buf.set_line(
+                area.left(),
+                area.bottom().saturating_sub(1),
+                &Line::from(spans),
+                area.width,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        app::confirm::ConfirmAction,
+        pleroma::tweet::{ContentType, Tweet, TweetSource, test::fixture},
+    };
+
+    use super::{Compose, ComposeFocus};
+
+    #[test]
+    fn redraft_deleted_posts() {
+        assert_eq!(
+            ConfirmAction::Delete("1".to_string(), true).prompt(),
+            "Delete this post and redraft it?"
+        );
+        // Just HTML from Mastodon
+        let mastodon = Compose::redraft(&fixture("mastodon_status.json"));
+        assert_eq!(
+            mastodon.text,
+            "Release day! Notes at https://example.org/notes"
+        );
+        let pleroma = Compose::redraft(&fixture("pleroma_status.json"));
+        assert_eq!(pleroma.text, "@bob agreed\nsee you there");
+        let gotosocial = Compose::redraft(&fixture("gotosocial_status.json"));
+        assert_eq!(gotosocial.text, "listening to this");
+    }
+
+    #[test]
+    fn edit_keeps_format_and_attachments() {
+        let tweet: Tweet = fixture("mastodon_status.json");
+        let source = TweetSource {
+            id: tweet.id.clone(),
+            text: "Release day!".to_string(),
+            spoiler_text: String::new(),
+            content_type: Some("text/markdown".to_string()),
+        };
+        let mut compose = Compose::edit(&tweet, source);
+        assert_eq!(compose.content_type, ContentType::Markdown);
+        assert_eq!(compose.attachment_count(), 1);
+
+        compose.cycle_focus();
+        compose.cycle_focus();
+        assert!(compose.focus == ComposeFocus::Kept(0));
+        assert!(compose.focused_text().is_none());
+        compose.remove_focused_attachment();
+        assert_eq!(compose.attachment_count(), 0);
+    }
+}
@@ -0,0 +1,56 @@
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Style},
+    widgets::Widget,
+};
+
+/// What happens once the user says yes
+#[derive(Clone)]
+pub enum ConfirmAction {
+    /// Deletes the post, reopening it in the composer if true
+    Delete(String, bool),
+    /// Drops the scheduled post before it gets published
+    CancelScheduled(String),
+    DeleteDraft(u64),
+    RemoveConversation(String),
+    DeleteList(String),
+}
+
+impl ConfirmAction {
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            ConfirmAction::Delete(_, false) => "Delete this post?",
+            ConfirmAction::Delete(_, true) => "Delete this post and redraft it?",
+            ConfirmAction::CancelScheduled(_) => "Cancel this scheduled post?",
+            ConfirmAction::DeleteDraft(_) => "Delete this draft?",
+            ConfirmAction::RemoveConversation(_) => "Remove this conversation?",
+            ConfirmAction::DeleteList(_) => "Delete this list?",
+        }
+    }
+}
+
+pub struct ConfirmWidget<'a> {
+    action: &'a ConfirmAction,
+}
+
+impl<'a> From<&'a ConfirmAction> for ConfirmWidget<'a> {
+    fn from(value: &'a ConfirmAction) -> Self {
+        ConfirmWidget { action: value }
+    }
+}
+
+impl Widget for ConfirmWidget<'_> {
+    /// Expecting to be 1 line
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        buf.set_stringn(
+            area.left(),
+            area.top(),
+            format!("{} [y/N]", self.action.prompt()),
+            area.width as usize,
+            Style::default().fg(Color::Yellow),
+        );
+    }
+}
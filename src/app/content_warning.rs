@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+
+use crate::{config::ContentWarningConfig, pleroma::tweet::Tweet};
+
+impl ContentWarningConfig {
+    fn collapsed_by_default(&self, tweet: &Tweet) -> bool {
+        if tweet.spoiler_text.is_empty() || self.always_expand {
+            return false;
+        }
+        let spoiler = tweet.spoiler_text.to_lowercase();
+        !self
+            .expand_matching
+            .iter()
+            .any(|w| spoiler.contains(&w.to_lowercase()))
+    }
+
+    fn media_hidden_by_default(&self, tweet: &Tweet) -> bool {
+        tweet.sensitive && !self.show_sensitive_media
+    }
+}
+
+/// Posts the user expanded or collapsed, by id. Toggling flips
+/// whatever the config decided for that post. Boosts go by the boosted
+/// post, the one with the CW.
+#[derive(Default)]
+pub struct Reveals {
+    content: HashSet<String>,
+    media: HashSet<String>,
+}
+
+fn toggle(set: &mut HashSet<String>, id: &str) {
+    if !set.remove(id) {
+        set.insert(id.to_string());
+    }
+}
+
+impl Reveals {
+    pub fn toggle_content(&mut self, id: &str) {
+        toggle(&mut self.content, id);
+    }
+
+    pub fn toggle_media(&mut self, id: &str) {
+        toggle(&mut self.media, id);
+    }
+
+    /// The content is hidden behind the CW
+    pub fn collapsed(&self, config: &ContentWarningConfig, tweet: &Tweet) -> bool {
+        let tweet = tweet.reblog.as_deref().unwrap_or(tweet);
+        !tweet.spoiler_text.is_empty()
+            && config.collapsed_by_default(tweet) != self.content.contains(&tweet.id)
+    }
+
+    pub fn media_hidden(&self, config: &ContentWarningConfig, tweet: &Tweet) -> bool {
+        let tweet = tweet.reblog.as_deref().unwrap_or(tweet);
+        self.collapsed(config, tweet)
+            || config.media_hidden_by_default(tweet) != self.media.contains(&tweet.id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        config::ContentWarningConfig,
+        pleroma::tweet::{Tweet, test::fixture},
+    };
+
+    use super::Reveals;
+
+    fn tweet(spoiler_text: &str, sensitive: bool) -> Tweet {
+        let mut tweet: Tweet = fixture("mastodon_status.json");
+        tweet.spoiler_text = spoiler_text.to_string();
+        tweet.sensitive = sensitive;
+        tweet
+    }
+
+    #[test]
+    fn toggles_flip_the_config() {
+        let config = ContentWarningConfig {
+            expand_matching: vec!["Spoilers".to_string()],
+            ..Default::default()
+        };
+        let mut reveals = Reveals::default();
+
+        let cw = tweet("politics", true);
+        assert!(reveals.collapsed(&config, &cw));
+        assert!(reveals.media_hidden(&config, &cw));
+        reveals.toggle_content(&cw.id);
+        assert!(!reveals.collapsed(&config, &cw));
+        assert!(reveals.media_hidden(&config, &cw));
+        reveals.toggle_media(&cw.id);
+        assert!(!reveals.media_hidden(&config, &cw));
+        reveals.toggle_content(&cw.id);
+        assert!(reveals.collapsed(&config, &cw));
+        assert!(reveals.media_hidden(&config, &cw));
+
+        // Expanded by the config, so toggling collapses it
+        let mut reveals = Reveals::default();
+        let matching = tweet("show SPOILERS", false);
+        assert!(!reveals.collapsed(&config, &matching));
+        assert!(!reveals.media_hidden(&config, &matching));
+        reveals.toggle_content(&matching.id);
+        assert!(reveals.collapsed(&config, &matching));
+
+        // A boost of it has the CW on the boosted post
+        let mut boost = tweet("", false);
+        boost.id = "boost".to_string();
+        boost.reblog = Some(Box::new(tweet("politics", true)));
+        let mut reveals = Reveals::default();
+        assert!(reveals.collapsed(&config, &boost));
+        assert!(reveals.media_hidden(&config, &boost));
+        reveals.toggle_content(&boost.reblog.as_ref().unwrap().id);
+        assert!(!reveals.collapsed(&config, &boost));
+
+        // Without a CW there's nothing to collapse
+        let plain = tweet("", false);
+        reveals.toggle_content(&plain.id);
+        assert!(!reveals.collapsed(&config, &plain));
+    }
+}
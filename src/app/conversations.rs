@@ -0,0 +1,159 @@
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::pleroma::conversation::Conversation;
+
+use super::emoji;
+
+/// Our direct message threads
+#[derive(Clone, Default)]
+pub struct ConversationList {
+    /// `None` until the server answers
+    pub conversations: Option<Vec<Conversation>>,
+    /// Cursor of the next page, `None` once all are loaded
+    pub next: Option<String>,
+    pub i: usize,
+}
+
+impl ConversationList {
+    pub fn selected(&self) -> Option<&Conversation> {
+        self.conversations.as_ref()?.get(self.i)
+    }
+
+    /// Returns the cursor of the next page when moving past the last one
+    pub fn down(&mut self) -> Option<String> {
+        let len = self.conversations.as_ref().map(|c| c.len()).unwrap_or(0);
+        if self.i + 1 < len {
+            self.i += 1;
+            None
+        } else {
+            self.next.take()
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.i = self.i.saturating_sub(1);
+    }
+
+    pub fn update(&mut self, conversation: Conversation) {
+        if let Some(c) = self
+            .conversations
+            .iter_mut()
+            .flatten()
+            .find(|c| c.id == conversation.id)
+        {
+            *c = conversation;
+        }
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        if let Some(conversations) = &mut self.conversations {
+            conversations.retain(|c| c.id != id);
+            self.i = self.i.min(conversations.len().saturating_sub(1));
+        }
+    }
+}
+
+pub struct ConversationListWidget<'a> {
+    list: &'a ConversationList,
+}
+
+impl<'a> From<&'a ConversationList> for ConversationListWidget<'a> {
+    fn from(value: &'a ConversationList) -> Self {
+        ConversationListWidget { list: value }
+    }
+}
+
+impl Widget for ConversationListWidget<'_> {
+    /// Two lines per conversation, the participants then the last message
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        buf.set_stringn(
+            area.left(),
+            area.top(),
+            "Direct messages | Open (Enter) | Remove (x) | Back (Esc)",
+            area.width as usize,
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+
+        let conversations = match &self.list.conversations {
+            Some(conversations) if conversations.is_empty() => {
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1,
+                    "No conversations",
+                    area.width as usize,
+                    Style::default().fg(Color::DarkGray),
+                );
+                return;
+            }
+            Some(conversations) => conversations,
+            None => {
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1,
+                    "Loading...",
+                    area.width as usize,
+                    Style::default().fg(Color::DarkGray),
+                );
+                return;
+            }
+        };
+
+        let rows = (area.height.saturating_sub(1) / 2) as usize;
+        let skip = (self.list.i + 1).saturating_sub(rows);
+        conversations
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(rows)
+            .for_each(|(i, conversation)| {
+                let y = area.top() + 1 + 2 * (i - skip) as u16;
+                let style = if i == self.list.i {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                let mut spans = vec![Span::default().content(if conversation.unread {
+                    "\u{25cf} "
+                } else {
+                    "  "
+                })];
+                for (n, account) in conversation.accounts.iter().enumerate() {
+                    if n > 0 {
+                        spans.push(Span::default().content(", ").style(style));
+                    }
+                    let name = match account.display_name.as_str() {
+                        "" => account.acct.as_str(),
+                        name => name,
+                    };
+                    let bold = if conversation.unread {
+                        style.add_modifier(Modifier::BOLD)
+                    } else {
+                        style
+                    };
+                    spans.extend(emoji::spans(name, &account.emojis, bold));
+                }
+                buf.set_line(area.left(), y, &Line::default().spans(spans), area.width);
+
+                let last = conversation
+                    .last_status
+                    .as_ref()
+                    .map(|t| format!("  @{}: {}", t.account.acct, t.plain_content()))
+                    .unwrap_or_default();
+                buf.set_stringn(
+                    area.left(),
+                    y + 1,
+                    last.replace('\n', " "),
+                    area.width as usize,
+                    Style::default().fg(Color::DarkGray),
+                );
+            });
+    }
+}
@@ -0,0 +1,180 @@
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::pleroma::{html::strip_html, tweet::TweetRevision};
+
+use super::time::absolute;
+
+#[derive(Debug, PartialEq)]
+pub enum Change<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Word by word differences between two texts, from the longest common
+/// subsequence of their words
+pub fn words<'a>(old: &'a str, new: &'a str) -> Vec<Change<'a>> {
+    let old: Vec<&str> = old.split_whitespace().collect();
+    let new: Vec<&str> = new.split_whitespace().collect();
+
+    // common[i][j] is the length of the LCS of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            changes.push(Change::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            changes.push(Change::Removed(old[i]));
+            i += 1;
+        } else {
+            changes.push(Change::Added(new[j]));
+            j += 1;
+        }
+    }
+    changes.extend(old[i..].iter().map(|w| Change::Removed(w)));
+    changes.extend(new[j..].iter().map(|w| Change::Added(w)));
+    changes
+}
+
+/// Revision `i` of a post compared with the one before it
+pub struct HistoryWidget<'a> {
+    revisions: &'a [TweetRevision],
+    i: usize,
+}
+
+impl<'a> HistoryWidget<'a> {
+    pub fn new(revisions: &'a [TweetRevision], i: usize) -> Self {
+        HistoryWidget { revisions, i }
+    }
+}
+
+impl Widget for HistoryWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let revision = match self.revisions.get(self.i) {
+            Some(revision) => revision,
+            None => return,
+        };
+        let previous = self.i.checked_sub(1).map(|i| &self.revisions[i]);
+
+        let header = format!(
+            "Revision {}/{} from {}{} [\u{2190}/\u{2192}]",
+            self.i + 1,
+            self.revisions.len(),
+            absolute(&revision.created_at),
+            if self.i == 0 { " (original)" } else { "" }
+        );
+        buf.set_stringn(
+            area.left(),
+            area.top(),
+            header,
+            area.width as usize,
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+
+        let removed = Style::default()
+            .fg(Color::Red)
+            .add_modifier(Modifier::CROSSED_OUT);
+        let added = Style::default().fg(Color::Green);
+        let spans = |old: &str, new: &str| -> Vec<Span<'static>> {
+            words(old, new)
+                .into_iter()
+                .flat_map(|change| {
+                    let (word, style) = match change {
+                        Change::Same(w) => (w, Style::default()),
+                        Change::Removed(w) => (w, removed),
+                        Change::Added(w) => (w, added),
+                    };
+                    [
+                        Span::default().content(word.to_string()).style(style),
+                        Span::default().content(" "),
+                    ]
+                })
+                .collect()
+        };
+
+        let mut lines = Vec::new();
+        let old_spoiler = previous.map(|p| p.spoiler_text.as_str()).unwrap_or("");
+        if !revision.spoiler_text.is_empty() || !old_spoiler.is_empty() {
+            let mut line = vec![
+                Span::default()
+                    .content("\u{26a0} ")
+                    .style(Style::default().fg(Color::Yellow)),
+            ];
+            line.extend(spans(old_spoiler, &revision.spoiler_text));
+            lines.push(Line::from(line));
+        }
+        let content = strip_html(&revision.content);
+        let old_content = previous.map(|p| strip_html(&p.content));
+        lines.push(Line::from(spans(
+            old_content.as_deref().unwrap_or(&content),
+            &content,
+        )));
+        let old_media = previous.map(|p| p.media_attachments.len());
+        if let Some(old_media) = old_media
+            && old_media != revision.media_attachments.len()
+        {
+            lines.push(Line::from(
+                Span::default()
+                    .content(format!(
+                        "{} \u{2192} {} attachments",
+                        old_media,
+                        revision.media_attachments.len()
+                    ))
+                    .style(Style::default().fg(Color::DarkGray)),
+            ));
+        }
+
+        Paragraph::new(Text::from(lines))
+            .wrap(Wrap { trim: false })
+            .render(
+                Rect::new(
+                    area.left(),
+                    area.top() + 1,
+                    area.width,
+                    area.height.saturating_sub(1),
+                ),
+                buf,
+            );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Change, words};
+
+    #[test]
+    fn word_diff() {
+        assert_eq!(
+            words("the quick fox jumps", "the slow fox jumps high"),
+            vec![
+                Change::Same("the"),
+                Change::Removed("quick"),
+                Change::Added("slow"),
+                Change::Same("fox"),
+                Change::Same("jumps"),
+                Change::Added("high"),
+            ]
+        );
+    }
+}
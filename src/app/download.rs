@@ -0,0 +1,106 @@
+use crate::pleroma::tweet::{MediaAttatchment, Tweet};
+
+/// Sent while attachments are being saved
+pub struct DownloadProgress {
+    /// 1 based index of the file being downloaded
+    pub file: usize,
+    pub files: usize,
+    pub downloaded: u64,
+    pub size: Option<u64>,
+}
+
+impl DownloadProgress {
+    pub fn describe(&self) -> String {
+        let mib = self.downloaded as f64 / (1024.0 * 1024.0);
+        match self.size {
+            Some(size) if size > 0 => format!(
+                "Downloading {}/{}: {}% ({:.1} MiB)",
+                self.file,
+                self.files,
+                self.downloaded * 100 / size,
+                mib
+            ),
+            _ => format!("Downloading {}/{}: {:.1} MiB", self.file, self.files, mib),
+        }
+    }
+}
+
+/// `<author>_<attachment id>.<extension>`, stable so that downloading
+/// the same post again finds the files already there.
+pub fn file_name(tweet: &Tweet, media: &MediaAttatchment) -> String {
+    let sanitize = |s: &str| {
+        s.chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>()
+    };
+    let path = media.url().split(['?', '#']).next().unwrap_or_default();
+    let extension = path
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| sanitize(ext))
+        .filter(|ext| !ext.is_empty())
+        .unwrap_or("bin".to_string());
+    format!(
+        "{}_{}.{}",
+        sanitize(&tweet.account.acct),
+        sanitize(media.id()),
+        extension
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pleroma::tweet::{MediaAttatchment, Tweet, test::fixture};
+
+    use super::{DownloadProgress, file_name};
+
+    fn media(url: &str) -> MediaAttatchment {
+        serde_json::from_value(serde_json::json!({
+            "id": "1/2",
+            "type": "image",
+            "url": url,
+            "preview_url": url,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn stable_file_names() {
+        let mut tweet: Tweet = fixture("mastodon_status.json");
+        assert_eq!(
+            file_name(&tweet, &tweet.media_attachments[0]),
+            "alice_111303470981234567.png"
+        );
+        tweet.account.acct = "bob@remote.example".to_string();
+        assert_eq!(
+            file_name(&tweet, &media("https://x.example/a.b/c.JPG?size=big#top")),
+            "bob_remote.example_1_2.JPG"
+        );
+        assert_eq!(
+            file_name(&tweet, &media("https://x.example/a.b/c")),
+            "bob_remote.example_1_2.bin"
+        );
+    }
+
+    #[test]
+    fn describe_progress() {
+        let mut progress = DownloadProgress {
+            file: 2,
+            files: 3,
+            downloaded: 3 * 1024 * 1024,
+            size: Some(12 * 1024 * 1024),
+        };
+        assert_eq!(progress.describe(), "Downloading 2/3: 25% (3.0 MiB)");
+        progress.size = None;
+        assert_eq!(progress.describe(), "Downloading 2/3: 3.0 MiB");
+        progress.size = Some(0);
+        assert_eq!(progress.describe(), "Downloading 2/3: 3.0 MiB");
+    }
+}
@@ -0,0 +1,198 @@
+use std::{
+    fs::{create_dir_all, read_to_string, rename, write},
+    io::ErrorKind,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Result, anyhow};
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::pleroma::tweet::{ContentType, Visibility};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DraftAttachment {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// An unsent post
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Draft {
+    /// Milliseconds since the epoch at creation, unique enough for
+    /// a single user
+    pub id: u64,
+    pub text: String,
+    #[serde(default)]
+    pub spoiler_text: String,
+    #[serde(default)]
+    pub visibility: Visibility,
+    #[serde(default)]
+    pub content_type: ContentType,
+    pub in_reply_to_id: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<DraftAttachment>,
+}
+
+impl Draft {
+    pub fn new_id() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default()
+    }
+}
+
+/// Drafts kept in `<data dir>/plerustax/drafts.toml`
+#[derive(Serialize, Deserialize, Default)]
+pub struct Drafts {
+    /// Most recently saved first
+    pub drafts: Vec<Draft>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl Drafts {
+    pub fn load() -> Result<Self> {
+        let path = dirs::data_dir()
+            .ok_or(anyhow!("No data directory available"))?
+            .join("plerustax")
+            .join("drafts.toml");
+        let mut drafts = match read_to_string(&path) {
+            Ok(data) => toml::from_str(&data)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => Drafts::default(),
+            Err(e) => return Err(e.into()),
+        };
+        drafts.path = Some(path);
+        Ok(drafts)
+    }
+
+    /// Writes to a temporary file first so a crash can't leave half a file
+    pub fn save(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("toml.tmp");
+        write(&tmp, toml::to_string(self)?)?;
+        rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Replaces the draft with the same id, moving it to the top
+    pub fn upsert(&mut self, draft: Draft) {
+        self.remove(draft.id);
+        self.drafts.insert(0, draft);
+    }
+
+    pub fn remove(&mut self, id: u64) {
+        self.drafts.retain(|d| d.id != id);
+    }
+}
+
+pub struct DraftsWidget<'a> {
+    drafts: &'a Drafts,
+    i: usize,
+}
+
+impl<'a> DraftsWidget<'a> {
+    pub fn new(drafts: &'a Drafts, i: usize) -> Self {
+        DraftsWidget { drafts, i }
+    }
+}
+
+impl Widget for DraftsWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        buf.set_stringn(
+            area.left(),
+            area.top(),
+            "Drafts | Resume (Enter) | Post (s) | Delete (x) | Back (Esc)",
+            area.width as usize,
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+        if self.drafts.drafts.is_empty() {
+            buf.set_stringn(
+                area.left(),
+                area.top() + 1,
+                "No drafts",
+                area.width as usize,
+                Style::default().fg(Color::DarkGray),
+            );
+            return;
+        }
+
+        let rows = area.height.saturating_sub(1) as usize;
+        let skip = (self.i + 1).saturating_sub(rows);
+        self.drafts
+            .drafts
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(rows)
+            .for_each(|(i, draft)| {
+                let cw = match draft.spoiler_text.as_str() {
+                    "" => String::new(),
+                    cw => format!("\u{26a0} {} ", cw),
+                };
+                let attachments = match draft.attachments.len() {
+                    0 => String::new(),
+                    n => format!(" \u{1f4ce}{}", n),
+                };
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1 + (i - skip) as u16,
+                    format!(
+                        "[{}] {}{}{}",
+                        draft.visibility.name(),
+                        cw,
+                        draft.text.replace('\n', " "),
+                        attachments
+                    ),
+                    area.width as usize,
+                    if i == self.i {
+                        Style::default().add_modifier(Modifier::REVERSED)
+                    } else {
+                        Style::default()
+                    },
+                );
+            });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Draft, Drafts};
+    use crate::pleroma::tweet::{ContentType, Visibility};
+
+    #[test]
+    fn drafts_round_trip() {
+        let mut drafts = Drafts::default();
+        drafts.upsert(Draft {
+            id: 1,
+            text: "hello".to_string(),
+            spoiler_text: String::new(),
+            visibility: Visibility::Unlisted,
+            content_type: ContentType::Markdown,
+            in_reply_to_id: None,
+            attachments: Vec::new(),
+        });
+        let data = toml::to_string(&drafts).unwrap();
+        let loaded: Drafts = toml::from_str(&data).unwrap();
+        assert_eq!(loaded.drafts.len(), 1);
+        assert_eq!(loaded.drafts[0].visibility, Visibility::Unlisted);
+        assert_eq!(loaded.drafts[0].content_type, ContentType::Markdown);
+        assert_eq!(loaded.drafts[0].in_reply_to_id, None);
+    }
+}
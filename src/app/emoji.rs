@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Style},
+    text::Span,
+    widgets::Widget,
+};
+
+use crate::{pleroma::emoji::CustomEmoji, renderer::image::GraphicsProtocol};
+
+use super::preview::{Preview, PreviewWidget};
+
+/// Cells taken by an inline emoji image
+const EMOJI_COLUMNS: u16 = 2;
+/// Longer shortcodes are cut in placeholders
+const PLACEHOLDER_LEN: usize = 8;
+
+/// Text with the known `:shortcode:`s picked out
+#[derive(Debug, PartialEq)]
+pub enum Segment<'a> {
+    Text(&'a str),
+    Emoji(&'a CustomEmoji),
+}
+
+pub fn is_shortcode(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+pub fn segments<'a>(text: &'a str, emojis: &'a [CustomEmoji]) -> Vec<Segment<'a>> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut from = 0;
+    while let Some(open) = text[from..].find(':').map(|i| i + from) {
+        let close = match text[open + 1..].find(':') {
+            Some(i) => open + 1 + i,
+            None => break,
+        };
+        let name = &text[open + 1..close];
+        match emojis.iter().find(|e| e.shortcode == name) {
+            Some(emoji) if is_shortcode(name) => {
+                if start < open {
+                    segments.push(Segment::Text(&text[start..open]));
+                }
+                segments.push(Segment::Emoji(emoji));
+                start = close + 1;
+                from = close + 1;
+            }
+            // The closing colon may open the next one
+            _ => from = close,
+        }
+    }
+    if start < text.len() {
+        segments.push(Segment::Text(&text[start..]));
+    }
+    segments
+}
+
+/// Stands for the emoji when its image can't be shown
+pub fn placeholder(emoji: &CustomEmoji) -> Span<'static> {
+    let name = if emoji.shortcode.chars().count() > PLACEHOLDER_LEN {
+        let short: String = emoji.shortcode.chars().take(PLACEHOLDER_LEN - 1).collect();
+        format!(":{}\u{2026}:", short)
+    } else {
+        format!(":{}:", emoji.shortcode)
+    };
+    Span::default()
+        .content(name)
+        .style(Style::default().fg(Color::Magenta))
+}
+
+/// `text` with placeholders for the known emoji
+pub fn spans<'a>(text: &'a str, emojis: &'a [CustomEmoji], style: Style) -> Vec<Span<'a>> {
+    segments(text, emojis)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => Span::default().content(text).style(style),
+            Segment::Emoji(emoji) => placeholder(emoji),
+        })
+        .collect()
+}
+
+/// Custom emoji of the instance for the shortcode being typed, those
+/// starting with it first
+pub fn completions<'a>(query: &str, emojis: &'a [CustomEmoji]) -> Vec<&'a CustomEmoji> {
+    let visible = emojis.iter().filter(|e| e.visible_in_picker);
+    let starting = visible.clone().filter(|e| e.shortcode.starts_with(query));
+    let containing =
+        visible.filter(|e| !e.shortcode.starts_with(query) && e.shortcode.contains(query));
+    starting.chain(containing).collect()
+}
+
+/// One line of text with custom emoji drawn inline when the terminal
+/// can show images, as placeholders otherwise
+pub struct EmojiText<'a> {
+    text: &'a str,
+    emojis: &'a [CustomEmoji],
+    style: Style,
+    images: Option<(&'a HashMap<String, Preview>, GraphicsProtocol)>,
+}
+
+impl<'a> EmojiText<'a> {
+    pub fn new(text: &'a str, emojis: &'a [CustomEmoji]) -> Self {
+        EmojiText {
+            text,
+            emojis,
+            style: Style::default(),
+            images: None,
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Half blocks are too coarse for a single row, those get placeholders
+    pub fn images(
+        mut self,
+        images: &'a HashMap<String, Preview>,
+        graphics: GraphicsProtocol,
+    ) -> Self {
+        if graphics != GraphicsProtocol::HalfBlock {
+            self.images = Some((images, graphics));
+        }
+        self
+    }
+}
+
+impl Widget for EmojiText<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let mut x = area.left();
+        for segment in segments(self.text, self.emojis) {
+            if x >= area.right() {
+                break;
+            }
+            let left = (area.right() - x) as usize;
+            match segment {
+                Segment::Text(text) => {
+                    x = buf.set_stringn(x, area.top(), text, left, self.style).0;
+                }
+                Segment::Emoji(emoji) => {
+                    let image = self.images.and_then(|(images, graphics)| {
+                        match images.get_key_value(&emoji.static_url) {
+                            Some((url, Preview::Ready(image))) => Some((url, image, graphics)),
+                            _ => None,
+                        }
+                    });
+                    match image {
+                        Some((url, image, graphics)) if left >= EMOJI_COLUMNS as usize => {
+                            PreviewWidget::new(image, graphics, url)
+                                .render(Rect::new(x, area.top(), EMOJI_COLUMNS, 1), buf);
+                            x += EMOJI_COLUMNS;
+                        }
+                        _ => {
+                            x = buf
+                                .set_span(x, area.top(), &placeholder(emoji), left as u16)
+                                .0;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Segment, segments};
+    use crate::pleroma::emoji::CustomEmoji;
+
+    fn emoji(shortcode: &str) -> CustomEmoji {
+        CustomEmoji {
+            shortcode: shortcode.to_string(),
+            url: String::new(),
+            static_url: String::new(),
+            visible_in_picker: true,
+            category: None,
+        }
+    }
+
+    #[test]
+    fn known_shortcodes() {
+        let emojis = [emoji("blobcat")];
+        assert_eq!(
+            segments("at 10:30 :blobcat::nope: :blobcat:", &emojis),
+            [
+                Segment::Text("at 10:30 "),
+                Segment::Emoji(&emojis[0]),
+                Segment::Text(":nope: "),
+                Segment::Emoji(&emojis[0]),
+            ]
+        );
+    }
+}
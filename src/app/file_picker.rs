@@ -0,0 +1,141 @@
+use std::{
+    env::current_dir,
+    fs::read_dir,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Style},
+    widgets::Widget,
+};
+
+/// Browses the local filesystem to pick a file
+#[derive(Clone)]
+pub struct FilePicker {
+    pub dir: PathBuf,
+    pub entries: Vec<PathBuf>,
+    pub i: usize,
+}
+
+impl FilePicker {
+    /// Starts from the current working directory
+    pub fn new() -> Result<Self> {
+        let dir = current_dir()?;
+        Ok(FilePicker {
+            entries: read_entries(&dir)?,
+            dir,
+            i: 0,
+        })
+    }
+
+    pub fn up(&mut self) {
+        if self.i > 0 {
+            self.i -= 1;
+        }
+    }
+
+    pub fn down(&mut self) {
+        if self.i + 1 < self.entries.len() {
+            self.i += 1;
+        }
+    }
+
+    pub fn parent(&mut self) -> Result<()> {
+        if let Some(parent) = self.dir.parent() {
+            let parent = parent.to_path_buf();
+            self.entries = read_entries(&parent)?;
+            self.dir = parent;
+            self.i = 0;
+        }
+        Ok(())
+    }
+
+    /// Enters the selected directory, or returns the selected file
+    pub fn select(&mut self) -> Result<Option<PathBuf>> {
+        let selected = match self.entries.get(self.i) {
+            Some(p) => p.clone(),
+            None => return Ok(None),
+        };
+        if selected.is_dir() {
+            self.entries = read_entries(&selected)?;
+            self.dir = selected;
+            self.i = 0;
+            Ok(None)
+        } else {
+            Ok(Some(selected))
+        }
+    }
+}
+
+/// Directories first, hidden files are skipped
+fn read_entries(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries = read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            !p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with('.'))
+        })
+        .collect::<Vec<PathBuf>>();
+    entries.sort_by(|a, b| b.is_dir().cmp(&a.is_dir()).then(a.cmp(b)));
+    Ok(entries)
+}
+
+pub struct FilePickerWidget<'a> {
+    picker: &'a FilePicker,
+}
+
+impl<'a> From<&'a FilePicker> for FilePickerWidget<'a> {
+    fn from(value: &'a FilePicker) -> Self {
+        FilePickerWidget { picker: value }
+    }
+}
+
+impl Widget for FilePickerWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        buf.set_stringn(
+            area.left(),
+            area.top(),
+            self.picker.dir.display().to_string(),
+            area.width as usize,
+            Style::default().fg(Color::Cyan),
+        );
+
+        let rows = area.height.saturating_sub(1) as usize;
+        let skip = (self.picker.i + 1).saturating_sub(rows);
+        self.picker
+            .entries
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(rows)
+            .enumerate()
+            .for_each(|(row, (i, entry))| {
+                let mut name = entry
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                if entry.is_dir() {
+                    name.push('/');
+                }
+                let style = if i == self.picker.i {
+                    Style::default().fg(Color::Black).bg(Color::White)
+                } else {
+                    Style::default()
+                };
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1 + row as u16,
+                    name,
+                    area.width as usize,
+                    style,
+                );
+            });
+    }
+}
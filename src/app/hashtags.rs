@@ -0,0 +1,153 @@
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+
+use crate::pleroma::{tag::Hashtag, tweet::TweetTag};
+
+/// Hashtags of a post, or the ones we follow
+#[derive(Clone, Default)]
+pub struct HashtagList {
+    /// `None` until the server answers
+    pub tags: Option<Vec<Hashtag>>,
+    /// Cursor of the next page of followed tags, `None` once all are loaded
+    pub next: Option<String>,
+    pub i: usize,
+    /// Query being typed, see `TagQuery`
+    pub input: Option<String>,
+    pub error: Option<String>,
+    /// Lists the hashtags we follow rather than those of a post
+    pub followed: bool,
+}
+
+impl HashtagList {
+    /// The hashtags of a post, `followed` telling which ones we follow
+    pub fn from_tags(tags: &[TweetTag], followed: &[String]) -> Self {
+        HashtagList {
+            tags: Some(
+                tags.iter()
+                    .map(|t| Hashtag {
+                        name: t.name.clone(),
+                        following: followed.iter().any(|f| f.eq_ignore_ascii_case(&t.name)),
+                    })
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    pub fn followed() -> Self {
+        HashtagList {
+            followed: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn selected(&self) -> Option<&Hashtag> {
+        self.tags.as_ref()?.get(self.i)
+    }
+
+    /// Returns the cursor of the next page when moving past the last one
+    pub fn down(&mut self) -> Option<String> {
+        let len = self.tags.as_ref().map(|t| t.len()).unwrap_or(0);
+        if self.i + 1 < len {
+            self.i += 1;
+            None
+        } else {
+            self.next.take()
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.i = self.i.saturating_sub(1);
+    }
+
+    /// Hashtag names aren't case sensitive
+    pub fn update(&mut self, tag: Hashtag) {
+        if let Some(t) = self
+            .tags
+            .iter_mut()
+            .flatten()
+            .find(|t| t.name.eq_ignore_ascii_case(&tag.name))
+        {
+            *t = tag;
+        }
+    }
+}
+
+pub struct HashtagListWidget<'a> {
+    list: &'a HashtagList,
+}
+
+impl<'a> From<&'a HashtagList> for HashtagListWidget<'a> {
+    fn from(value: &'a HashtagList) -> Self {
+        HashtagListWidget { list: value }
+    }
+}
+
+impl Widget for HashtagListWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let (header, style) = match (&self.list.error, &self.list.input) {
+            (Some(error), _) => (error.clone(), Style::default().fg(Color::Red)),
+            (None, Some(input)) => (
+                format!("Query (any:a,b all:c none:d local, Enter): {}", input),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            (None, None) => (
+                "Hashtags | Open (Enter) | Query (/) | Follow (f) | Back (Esc)".to_string(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+        };
+        buf.set_stringn(area.left(), area.top(), header, area.width as usize, style);
+
+        let tags = match &self.list.tags {
+            Some(tags) if tags.is_empty() => {
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1,
+                    "No hashtags",
+                    area.width as usize,
+                    Style::default().fg(Color::DarkGray),
+                );
+                return;
+            }
+            Some(tags) => tags,
+            None => {
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1,
+                    "Loading...",
+                    area.width as usize,
+                    Style::default().fg(Color::DarkGray),
+                );
+                return;
+            }
+        };
+
+        let rows = area.height.saturating_sub(1) as usize;
+        let skip = (self.list.i + 1).saturating_sub(rows);
+        tags.iter()
+            .enumerate()
+            .skip(skip)
+            .take(rows)
+            .for_each(|(i, tag)| {
+                let style = if i == self.list.i {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                let following = if tag.following { " [following]" } else { "" };
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1 + (i - skip) as u16,
+                    format!("#{}{}", tag.name, following),
+                    area.width as usize,
+                    style,
+                );
+            });
+    }
+}
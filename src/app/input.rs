@@ -1,26 +1,105 @@
-use std::time::Duration;
+use std::{
+    sync::{Condvar, Mutex, MutexGuard},
+    time::Duration,
+};
 
 use anyhow::Result;
-use cli_log::info;
+use chrono::Utc;
+use cli_log::{info, warn};
 use ratatui::crossterm::event::{self, Event, KeyCode, KeyModifiers, poll};
-use tokio::sync::mpsc::Sender;
+use tokio::{sync::mpsc::Sender, task::block_in_place};
 
 use crate::TICK_RATE;
 
+use crate::pleroma::{
+    media::MediaUpload,
+    tag::TagQuery,
+    tweet::{ContentType, NewTweet, Visibility},
+};
+
 use super::{
     App,
+    accounts::{AccountList, AccountListKind},
+    chats::{ChatList, ChatWindow},
+    compose::{Compose, ComposeFocus},
+    confirm::ConfirmAction,
+    conversations::ConversationList,
+    emoji::completions,
+    file_picker::FilePicker,
+    hashtags::HashtagList,
+    lists::{ListInput, ListManager},
     message::Message,
+    open::{OpenTarget, open},
+    reactions::ReactionPicker,
+    scheduled::ScheduledList,
     state::{State, Timeline},
+    status::Status,
+    time::schedule,
 };
 
-pub async fn input_generator(app: Sender<Message>) -> Result<()> {
+/// Handshake between `open` and the input thread
+struct InputPause {
+    /// An external program wants the terminal
+    requested: bool,
+    /// The input thread isn't polling, so events reach the program
+    idle: bool,
+}
+
+static INPUT_PAUSE: Mutex<InputPause> = Mutex::new(InputPause {
+    requested: false,
+    idle: true,
+});
+static INPUT_PAUSE_CHANGED: Condvar = Condvar::new();
+
+fn input_pause() -> MutexGuard<'static, InputPause> {
+    INPUT_PAUSE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Stops reading events, so that they reach the external program instead.
+/// Returns once the input thread is done with its current poll.
+pub fn pause_input() {
+    block_in_place(|| {
+        let mut pause = input_pause();
+        pause.requested = true;
+        let _idle = INPUT_PAUSE_CHANGED
+            .wait_while(pause, |p| !p.idle)
+            .unwrap_or_else(|e| e.into_inner());
+    });
+}
+
+pub fn resume_input() {
+    input_pause().requested = false;
+    INPUT_PAUSE_CHANGED.notify_all();
+}
+
+/// Waits while paused, marking the thread idle meanwhile
+fn wait_for_input() {
+    let mut pause = input_pause();
+    pause.idle = true;
+    INPUT_PAUSE_CHANGED.notify_all();
+    pause = INPUT_PAUSE_CHANGED
+        .wait_while(pause, |p| p.requested)
+        .unwrap_or_else(|e| e.into_inner());
+    pause.idle = false;
+}
+
+/// Reads terminal events on a blocking thread
+pub fn input_generator(app: Sender<Message>) -> Result<()> {
+    let result = read_events(&app);
+    // Nobody should wait for a thread that stopped
+    input_pause().idle = true;
+    INPUT_PAUSE_CHANGED.notify_all();
+    result
+}
+
+fn read_events(app: &Sender<Message>) -> Result<()> {
     while !app.is_closed() {
-        async {}.await;
+        wait_for_input();
         if poll(Duration::from_millis(TICK_RATE))? {
             let event = event::read()?;
             info!("Sending event");
             info!("{:?}", event);
-            app.send(Message::Input(event)).await?;
+            app.blocking_send(Message::Input(event))?;
         }
     }
     Ok(())
@@ -42,28 +121,271 @@ pub async fn handle_input(app: &mut App, event: Event) -> Result<()> {
 
     info!("Before match state");
     match app.state.clone() {
-        State::Timeline(_, _) => handle_timeline(app, event),
+        State::Timeline(_, _) => handle_timeline(app, event).await,
+        State::Compose(_) => handle_compose(app, event).await,
+        State::OpenPicker(_, _) => handle_open_picker(app, event),
+        State::Confirm(_) => handle_confirm(app, event).await,
+        State::History(_, _) => handle_history(app, event),
+        State::Scheduled(_) => handle_scheduled(app, event).await,
+        State::Drafts(_) => handle_drafts(app, event).await,
+        State::ReactionPicker(_) => handle_reaction_picker(app, event).await,
+        State::Accounts(_) => handle_accounts(app, event).await,
+        State::Thread(_) => handle_thread(app, event).await,
+        State::Conversations(_) => handle_conversations(app, event).await,
+        State::Chats(_) => handle_chats(app, event).await,
+        State::Chat(_) => handle_chat(app, event).await,
+        State::Lists(_) => handle_lists(app, event).await,
+        State::Hashtags(_) => handle_hashtags(app, event).await,
+        State::Profile(account) => {
+            if let Event::Key(key_event) = event {
+                match key_event.code {
+                    KeyCode::Char('c') => {
+                        let message = Message::OpenChat(account.id.clone());
+                        app.backend_chan.as_ref().unwrap().send(message).await?;
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') => app.pop_state(),
+                    _ => (),
+                }
+            }
+            Ok(())
+        }
     }
 }
 
 /// TODO: Fetch new tweets on list edge
-fn handle_timeline(app: &mut App, event: Event) -> Result<()> {
+async fn handle_timeline(app: &mut App, event: Event) -> Result<()> {
     info!("In timeline input handling");
     match event {
         Event::Key(key_event) => match key_event.code {
             KeyCode::Down => match &mut app.state {
                 State::Timeline(t, i) => {
-                    let t_len = match t {
-                        Timeline::Home => app.timelines.home.len(),
-                        Timeline::Local => app.timelines.local.len(),
-                        Timeline::Public => app.timelines.public.len(),
-                    };
-                    if *i < t_len - 1 {
+                    let t_len = app.timelines.get(t).len();
+                    if *i + 1 < t_len {
                         *i = *i + 1;
+                    } else if let Some(message) = app.timelines.next_page(t) {
+                        app.backend_chan.as_ref().unwrap().send(message).await?;
                     }
                 }
                 _ => unreachable!(),
             },
+            KeyCode::Char(c @ '1'..='9') => {
+                let tab = app.tabs.get(c as usize - '1' as usize).cloned();
+                if let Some(timeline) = tab {
+                    app.switch_timeline(timeline).await?;
+                }
+            }
+            KeyCode::Char('w') => {
+                if let State::Timeline(t, _) = &app.state
+                    && !Timeline::BUILT_IN.iter().any(|b| b.is(t))
+                {
+                    let t = t.clone();
+                    app.tabs.retain(|tab| !tab.is(&t));
+                    app.switch_timeline(Timeline::Home).await?;
+                }
+            }
+            KeyCode::Char('t') => {
+                if let State::Timeline(Timeline::Tag(query), _) = &app.state {
+                    let mut toggled = query.clone();
+                    toggled.local = !toggled.local;
+                    let pinned = app.is_pinned(query);
+                    let current = Timeline::Tag(query.clone());
+                    let toggled = Timeline::Tag(toggled);
+                    // Pinned tabs stay, the other one opens next to them
+                    if !pinned {
+                        if app.tabs.iter().any(|t| t.is(&toggled)) {
+                            app.tabs.retain(|t| !t.is(&current));
+                        } else if let Some(tab) = app.tabs.iter_mut().find(|t| t.is(&current)) {
+                            *tab = toggled.clone();
+                        }
+                    }
+                    app.switch_timeline(toggled).await?;
+                }
+            }
+            KeyCode::Char('#') => {
+                if let Some(tweet) = app.selected_tweet() {
+                    let tweet = tweet.reblog.as_deref().unwrap_or(tweet);
+                    if !tweet.tags.is_empty() {
+                        let list = HashtagList::from_tags(&tweet.tags, &app.followed_tags);
+                        app.push_state(State::Hashtags(list));
+                    }
+                }
+            }
+            KeyCode::Char('H') => {
+                app.push_state(State::Hashtags(HashtagList::followed()));
+                app.backend_chan
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::GetFollowedTags(None))
+                    .await?;
+            }
+            KeyCode::Char('L') => {
+                app.push_state(State::Lists(ListManager::default()));
+                app.backend_chan
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::GetLists)
+                    .await?;
+            }
+            KeyCode::Char('A') => {
+                if let Some(tweet) = app.selected_tweet() {
+                    let tweet = tweet.reblog.as_deref().unwrap_or(tweet);
+                    let manager = ListManager::for_account(tweet.account.clone());
+                    app.push_state(State::Lists(manager));
+                    app.backend_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetLists)
+                        .await?;
+                }
+            }
+            KeyCode::Char(c @ ('F' | 'B')) => {
+                if let Some(tweet) = app.selected_tweet() {
+                    let tweet = tweet.reblog.as_deref().unwrap_or(tweet);
+                    let kind = if c == 'F' {
+                        AccountListKind::FavouritedBy
+                    } else {
+                        AccountListKind::RebloggedBy
+                    };
+                    let id = tweet.id.clone();
+                    app.push_state(State::Accounts(AccountList::new(kind, id.clone())));
+                    app.backend_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetTweetAccounts(kind, id, None))
+                        .await?;
+                }
+            }
+            KeyCode::Char('E') => {
+                if let Some(tweet) = app.selected_tweet() {
+                    let tweet = tweet.reblog.as_deref().unwrap_or(tweet);
+                    let picker = ReactionPicker::new(tweet, &app.custom_emojis);
+                    app.push_state(State::ReactionPicker(picker));
+                }
+            }
+            KeyCode::Char('b') => {
+                if let Some(tweet) = app.selected_tweet() {
+                    let tweet = tweet.reblog.as_deref().unwrap_or(tweet);
+                    let message = Message::BookmarkTweet(tweet.id.clone(), !tweet.bookmarked);
+                    app.backend_chan.as_ref().unwrap().send(message).await?;
+                }
+            }
+            KeyCode::Char('n') => app.push_state(State::Compose(Compose::default())),
+            KeyCode::Char('r') => reply(app),
+            KeyCode::Enter => open_thread(app).await?,
+            KeyCode::Char(c @ ('d' | 'D')) => {
+                if let Some(tweet) = app.selected_tweet() {
+                    let id = tweet.id.clone();
+                    app.status = Some(Status::Info("Downloading...".to_string()));
+                    app.backend_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::DownloadMedia(
+                            id,
+                            c == 'D',
+                            app.config.download.directory.clone(),
+                        ))
+                        .await?;
+                }
+            }
+            KeyCode::Char(c @ ('x' | 'X')) => {
+                if let Some(tweet) = app.selected_tweet()
+                    && app.is_own(tweet)
+                {
+                    let id = tweet.id.clone();
+                    app.push_state(State::Confirm(ConfirmAction::Delete(id, c == 'X')));
+                }
+            }
+            KeyCode::Char('N') => app.push_state(State::Drafts(0)),
+            KeyCode::Char('C') => {
+                app.push_state(State::Conversations(ConversationList::default()));
+                app.backend_chan
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::GetConversations(None))
+                    .await?;
+            }
+            KeyCode::Char('M') => {
+                app.push_state(State::Chats(ChatList::default()));
+                app.backend_chan
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::GetChats)
+                    .await?;
+            }
+            KeyCode::Char('S') => {
+                app.push_state(State::Scheduled(ScheduledList::default()));
+                app.backend_chan
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::GetScheduledTweets)
+                    .await?;
+            }
+            KeyCode::Char('e') => {
+                if let Some(tweet) = app.selected_tweet()
+                    && app.is_own(tweet)
+                {
+                    let id = tweet.id.clone();
+                    app.backend_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::GetTweetSource(id))
+                        .await?;
+                }
+            }
+            KeyCode::Char('h') => {
+                if let Some(tweet) = app.selected_tweet() {
+                    let tweet = tweet.reblog.as_deref().unwrap_or(tweet);
+                    if tweet.edited_at.is_some() {
+                        let id = tweet.id.clone();
+                        app.backend_chan
+                            .as_ref()
+                            .unwrap()
+                            .send(Message::GetTweetHistory(id))
+                            .await?;
+                    }
+                }
+            }
+            KeyCode::Char('c') => {
+                if let Some(tweet) = app.selected_tweet() {
+                    let tweet = tweet.reblog.as_deref().unwrap_or(tweet).clone();
+                    app.reveals.toggle_content(&tweet.id);
+                    // Thumbnails of hidden media wait until it's revealed
+                    app.request_previews(&[tweet]).await?;
+                }
+            }
+            KeyCode::Char('v') => {
+                if let Some(tweet) = app.selected_tweet() {
+                    let tweet = tweet.reblog.as_deref().unwrap_or(tweet).clone();
+                    app.reveals.toggle_media(&tweet.id);
+                    // Thumbnails of hidden media wait until it's revealed
+                    app.request_previews(&[tweet]).await?;
+                }
+            }
+            KeyCode::Char('o') => {
+                if let Some(tweet) = app.selected_tweet() {
+                    let tweet = tweet.reblog.as_deref().unwrap_or(tweet);
+                    let target = OpenTarget::link(&tweet.uri);
+                    open_target(app, &target);
+                }
+            }
+            KeyCode::Char('l') => {
+                if let Some(tweet) = app.selected_tweet() {
+                    let tweet = tweet.reblog.as_deref().unwrap_or(tweet);
+                    let targets = tweet.links().iter().map(|l| OpenTarget::link(l)).collect();
+                    choose_target(app, targets);
+                }
+            }
+            KeyCode::Char('m') => {
+                if let Some(tweet) = app.selected_tweet() {
+                    let tweet = tweet.reblog.as_deref().unwrap_or(tweet);
+                    let targets = tweet
+                        .media_attachments
+                        .iter()
+                        .map(OpenTarget::from)
+                        .collect();
+                    choose_target(app, targets);
+                }
+            }
             KeyCode::Up => match &mut app.state {
                 State::Timeline(t, i) => {
                     if *i > 0 {
@@ -78,3 +400,789 @@ fn handle_timeline(app: &mut App, event: Event) -> Result<()> {
     }
     Ok(())
 }
+
+fn open_target(app: &mut App, target: &OpenTarget) {
+    if let Err(e) = open(&mut app.terminal, &app.config.open, target) {
+        warn!("Couldn't open {}: {}", target.url, e);
+        app.status = Some(Status::Error(format!(
+            "Couldn't open {}: {}",
+            target.url, e
+        )));
+    }
+}
+
+/// Opens the target right away when there's no choice to make
+fn choose_target(app: &mut App, targets: Vec<OpenTarget>) {
+    match targets.len() {
+        0 => (),
+        1 => open_target(app, &targets[0]),
+        _ => app.push_state(State::OpenPicker(targets, 0)),
+    }
+}
+
+fn handle_open_picker(app: &mut App, event: Event) -> Result<()> {
+    let (targets, i) = match &mut app.state {
+        State::OpenPicker(targets, i) => (targets, i),
+        _ => unreachable!(),
+    };
+    let key_event = match event {
+        Event::Key(e) => e,
+        _ => return Ok(()),
+    };
+
+    let chosen = match key_event.code {
+        KeyCode::Up => {
+            *i = i.saturating_sub(1);
+            None
+        }
+        KeyCode::Down => {
+            if *i + 1 < targets.len() {
+                *i += 1;
+            }
+            None
+        }
+        KeyCode::Enter => targets.get(*i).cloned(),
+        KeyCode::Char(c) if c.is_ascii_digit() && c != '0' => {
+            targets.get(c as usize - '1' as usize).cloned()
+        }
+        KeyCode::Esc => {
+            app.pop_state();
+            None
+        }
+        _ => None,
+    };
+    if let Some(target) = chosen {
+        app.pop_state();
+        open_target(app, &target);
+    }
+    Ok(())
+}
+
+async fn handle_confirm(app: &mut App, event: Event) -> Result<()> {
+    let action = match &app.state {
+        State::Confirm(action) => action.clone(),
+        _ => unreachable!(),
+    };
+    let key_event = match event {
+        Event::Key(e) => e,
+        _ => return Ok(()),
+    };
+
+    app.pop_state();
+    if let KeyCode::Char('y' | 'Y') = key_event.code {
+        match action {
+            ConfirmAction::Delete(id, redraft) => {
+                app.backend_chan
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::DeleteTweet(id, redraft))
+                    .await?;
+            }
+            ConfirmAction::CancelScheduled(id) => {
+                app.backend_chan
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::CancelScheduledTweet(id))
+                    .await?;
+            }
+            ConfirmAction::RemoveConversation(id) => {
+                app.backend_chan
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::RemoveConversation(id))
+                    .await?;
+            }
+            ConfirmAction::DeleteList(id) => {
+                app.backend_chan
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::DeleteList(id))
+                    .await?;
+            }
+            ConfirmAction::DeleteDraft(id) => {
+                app.drafts.remove(id);
+                if let State::Drafts(i) = &mut app.state {
+                    *i = (*i).min(app.drafts.drafts.len().saturating_sub(1));
+                }
+                if let Err(e) = app.drafts.save() {
+                    app.status = Some(Status::Error(format!("Couldn't save drafts: {}", e)));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_history(app: &mut App, event: Event) -> Result<()> {
+    let key_event = match event {
+        Event::Key(e) => e,
+        _ => return Ok(()),
+    };
+    let (revisions, i) = match &mut app.state {
+        State::History(revisions, i) => (revisions, i),
+        _ => unreachable!(),
+    };
+
+    match key_event.code {
+        KeyCode::Left if *i > 0 => *i -= 1,
+        KeyCode::Right if *i + 1 < revisions.len() => *i += 1,
+        KeyCode::Esc | KeyCode::Char('q') => app.pop_state(),
+        _ => (),
+    }
+    Ok(())
+}
+
+async fn handle_scheduled(app: &mut App, event: Event) -> Result<()> {
+    let key_event = match event {
+        Event::Key(e) => e,
+        _ => return Ok(()),
+    };
+    let list = match &mut app.state {
+        State::Scheduled(list) => list,
+        _ => unreachable!(),
+    };
+
+    if let Some(at) = &mut list.reschedule {
+        match key_event.code {
+            KeyCode::Esc => list.reschedule = None,
+            KeyCode::Backspace => {
+                at.pop();
+            }
+            KeyCode::Char(c) => at.push(c),
+            KeyCode::Enter => {
+                let at = match schedule(at, &Utc::now()) {
+                    Ok(at) => at,
+                    Err(e) => {
+                        app.status = Some(Status::Error(e.to_string()));
+                        return Ok(());
+                    }
+                };
+                list.reschedule = None;
+                if let Some(tweet) = list.selected() {
+                    let id = tweet.id.clone();
+                    app.backend_chan
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::RescheduleTweet(id, at))
+                        .await?;
+                }
+            }
+            _ => (),
+        }
+        return Ok(());
+    }
+
+    match key_event.code {
+        KeyCode::Down => list.down(),
+        KeyCode::Up => list.up(),
+        KeyCode::Char('r') => {
+            if let Some(tweet) = list.selected() {
+                list.reschedule = Some(tweet.scheduled_at.clone());
+            }
+        }
+        KeyCode::Char('x') => {
+            if let Some(tweet) = list.selected() {
+                let id = tweet.id.clone();
+                app.push_state(State::Confirm(ConfirmAction::CancelScheduled(id)));
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('q') => app.pop_state(),
+        _ => (),
+    }
+    Ok(())
+}
+
+async fn handle_drafts(app: &mut App, event: Event) -> Result<()> {
+    let key_event = match event {
+        Event::Key(e) => e,
+        _ => return Ok(()),
+    };
+    let i = match &mut app.state {
+        State::Drafts(i) => i,
+        _ => unreachable!(),
+    };
+
+    match key_event.code {
+        KeyCode::Down if *i + 1 < app.drafts.drafts.len() => *i += 1,
+        KeyCode::Up => *i = i.saturating_sub(1),
+        KeyCode::Enter => {
+            if let Some(draft) = app.drafts.drafts.get(*i) {
+                app.push_state(State::Compose(Compose::from_draft(draft)));
+            }
+        }
+        KeyCode::Char('s') => {
+            if let Some(draft) = app.drafts.drafts.get(*i) {
+                app.push_state(State::Compose(Compose::from_draft(draft)));
+                send_compose(app).await?;
+            }
+        }
+        KeyCode::Char('x') => {
+            if let Some(draft) = app.drafts.drafts.get(*i) {
+                let id = draft.id;
+                app.push_state(State::Confirm(ConfirmAction::DeleteDraft(id)));
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('q') => app.pop_state(),
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Publishes the post being written, or saves the edit
+async fn send_compose(app: &mut App) -> Result<()> {
+    let compose = match &mut app.state {
+        State::Compose(c) => c,
+        _ => unreachable!(),
+    };
+    if let Some(instance) = &app.instance_info {
+        if !instance.post_formats().contains(&compose.content_type) {
+            compose.error = Some(format!(
+                "The instance doesn't accept {}",
+                compose.content_type.name()
+            ));
+            return Ok(());
+        }
+        if let Err(e) = instance.check_attachments(compose.kept_media.len(), &compose.attachments) {
+            compose.error = Some(e.to_string());
+            return Ok(());
+        }
+    }
+    let scheduled_at = match compose.scheduled_at.trim() {
+        "" => None,
+        at => match schedule(at, &Utc::now()) {
+            Ok(at) => Some(at),
+            Err(e) => {
+                compose.error = Some(e.to_string());
+                return Ok(());
+            }
+        },
+    };
+    compose.sending = true;
+    let tweet = NewTweet::new(&compose.text)
+        .visibility(compose.visibility)
+        .content_type(compose.content_type)
+        .spoiler_text(&compose.spoiler_text)
+        .in_reply_to_id(compose.in_reply_to_id.clone())
+        .media_ids(
+            compose
+                .kept_media
+                .iter()
+                .map(|m| m.id().to_string())
+                .collect(),
+        )
+        .scheduled_at(scheduled_at);
+    let attachments = compose.attachments.clone();
+    let message = match &compose.editing {
+        Some(id) => Message::EditTweet(id.clone(), tweet, attachments),
+        None => Message::PostTweet(tweet, attachments),
+    };
+    app.backend_chan.as_ref().unwrap().send(message).await?;
+    Ok(())
+}
+
+/// Replies to the selected post, or to the one it boosts
+fn reply(app: &mut App) {
+    if let Some(tweet) = app.selected_tweet() {
+        let tweet = tweet.reblog.as_deref().unwrap_or(tweet);
+        let me = app.me.as_ref().map(|me| me.acct.as_str());
+        let mut compose = Compose::reply(tweet, me);
+        if let State::Thread(thread) = &app.state
+            && thread.direct
+        {
+            compose.visibility = Visibility::Direct;
+        }
+        app.push_state(State::Compose(compose));
+    }
+}
+
+async fn open_thread(app: &mut App) -> Result<()> {
+    if let Some(tweet) = app.selected_tweet() {
+        let tweet = tweet.reblog.as_deref().unwrap_or(tweet);
+        let message = Message::GetThread(tweet.id.clone());
+        app.backend_chan.as_ref().unwrap().send(message).await?;
+    }
+    Ok(())
+}
+
+async fn handle_thread(app: &mut App, event: Event) -> Result<()> {
+    let key_event = match event {
+        Event::Key(e) => e,
+        _ => return Ok(()),
+    };
+    let thread = match &mut app.state {
+        State::Thread(thread) => thread,
+        _ => unreachable!(),
+    };
+
+    match key_event.code {
+        KeyCode::Down => thread.down(),
+        KeyCode::Up => thread.up(),
+        KeyCode::Enter if thread.i != thread.focus => open_thread(app).await?,
+        KeyCode::Char('r') => reply(app),
+        KeyCode::Char('l') => {
+            if let Some(tweet) = app.selected_tweet() {
+                let targets = tweet.links().iter().map(|l| OpenTarget::link(l)).collect();
+                choose_target(app, targets);
+            }
+        }
+        KeyCode::Char('o') => {
+            if let Some(tweet) = app.selected_tweet() {
+                let target = OpenTarget::link(&tweet.uri);
+                open_target(app, &target);
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('q') => app.pop_state(),
+        _ => (),
+    }
+    Ok(())
+}
+
+async fn handle_conversations(app: &mut App, event: Event) -> Result<()> {
+    let key_event = match event {
+        Event::Key(e) => e,
+        _ => return Ok(()),
+    };
+    let list = match &mut app.state {
+        State::Conversations(list) => list,
+        _ => unreachable!(),
+    };
+
+    match key_event.code {
+        KeyCode::Down => {
+            if let Some(next) = list.down() {
+                let message = Message::GetConversations(Some(next));
+                app.backend_chan.as_ref().unwrap().send(message).await?;
+            }
+        }
+        KeyCode::Up => list.up(),
+        KeyCode::Enter => {
+            if let Some(conversation) = list.selected() {
+                let id = conversation.id.clone();
+                let unread = conversation.unread;
+                let last = conversation.last_status.as_ref().map(|t| t.id.clone());
+                if unread {
+                    let message = Message::ReadConversation(id);
+                    app.backend_chan.as_ref().unwrap().send(message).await?;
+                }
+                if let Some(last) = last {
+                    let message = Message::GetThread(last);
+                    app.backend_chan.as_ref().unwrap().send(message).await?;
+                }
+            }
+        }
+        KeyCode::Char('x') => {
+            if let Some(conversation) = list.selected() {
+                let id = conversation.id.clone();
+                app.push_state(State::Confirm(ConfirmAction::RemoveConversation(id)));
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('q') => app.pop_state(),
+        _ => (),
+    }
+    Ok(())
+}
+
+async fn handle_chats(app: &mut App, event: Event) -> Result<()> {
+    let key_event = match event {
+        Event::Key(e) => e,
+        _ => return Ok(()),
+    };
+    let list = match &mut app.state {
+        State::Chats(list) => list,
+        _ => unreachable!(),
+    };
+
+    match key_event.code {
+        KeyCode::Down => list.down(),
+        KeyCode::Up => list.up(),
+        KeyCode::Enter => {
+            if let Some(chat) = list.selected() {
+                let window = ChatWindow::new(chat.clone());
+                let message = Message::GetChatMessages(chat.id.clone(), None);
+                app.push_state(State::Chat(Box::new(window)));
+                app.backend_chan.as_ref().unwrap().send(message).await?;
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('q') => app.pop_state(),
+        _ => (),
+    }
+    Ok(())
+}
+
+async fn handle_chat(app: &mut App, event: Event) -> Result<()> {
+    let window = match &mut app.state {
+        State::Chat(window) => window,
+        _ => unreachable!(),
+    };
+    let key_event = match event {
+        Event::Key(e) => e,
+        _ => return Ok(()),
+    };
+    // Leaving is fine, a failure then shows up in the status bar
+    if window.sending && key_event.code != KeyCode::Esc {
+        return Ok(());
+    }
+
+    if let Some(picker) = &mut window.picker {
+        let res = match key_event.code {
+            KeyCode::Up => {
+                picker.up();
+                Ok(())
+            }
+            KeyCode::Down => {
+                picker.down();
+                Ok(())
+            }
+            KeyCode::Left | KeyCode::Backspace => picker.parent(),
+            KeyCode::Right | KeyCode::Enter => picker.select().map(|selected| {
+                if let Some(path) = selected {
+                    window.picker = None;
+                    window.attachment = Some(MediaUpload::new(path));
+                }
+            }),
+            KeyCode::Esc => {
+                window.picker = None;
+                Ok(())
+            }
+            _ => Ok(()),
+        };
+        if let Err(e) = res {
+            window.error = Some(e.to_string());
+        }
+        return Ok(());
+    }
+
+    window.error = None;
+    match (key_event.code, key_event.modifiers) {
+        (KeyCode::Esc, _) => app.pop_state(),
+        (KeyCode::Char('o'), KeyModifiers::CONTROL) => match FilePicker::new() {
+            Ok(picker) => window.picker = Some(picker),
+            Err(e) => window.error = Some(e.to_string()),
+        },
+        (KeyCode::Char('x'), KeyModifiers::CONTROL) => window.attachment = None,
+        (KeyCode::Up, _) => {
+            if let Some(next) = window.up() {
+                let message = Message::GetChatMessages(window.chat.id.clone(), Some(next));
+                app.backend_chan.as_ref().unwrap().send(message).await?;
+            }
+        }
+        (KeyCode::Down, _) => window.down(),
+        (KeyCode::Enter, _) => {
+            if window.input.trim().is_empty() && window.attachment.is_none() {
+                return Ok(());
+            }
+            window.sending = true;
+            let message = Message::SendToChat(
+                window.chat.id.clone(),
+                window.input.trim().to_string(),
+                window.attachment.clone(),
+            );
+            app.backend_chan.as_ref().unwrap().send(message).await?;
+        }
+        (KeyCode::Backspace, _) => {
+            window.input.pop();
+        }
+        (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => window.input.push(c),
+        _ => (),
+    }
+    Ok(())
+}
+
+async fn handle_hashtags(app: &mut App, event: Event) -> Result<()> {
+    let key_event = match event {
+        Event::Key(e) => e,
+        _ => return Ok(()),
+    };
+    let list = match &mut app.state {
+        State::Hashtags(list) => list,
+        _ => unreachable!(),
+    };
+
+    list.error = None;
+    if let Some(input) = &mut list.input {
+        match key_event.code {
+            KeyCode::Enter => match input.parse::<TagQuery>() {
+                Ok(query) => {
+                    app.pop_state();
+                    app.switch_timeline(Timeline::Tag(query)).await?;
+                }
+                Err(e) => list.error = Some(e.to_string()),
+            },
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Esc => list.input = None,
+            _ => (),
+        }
+        return Ok(());
+    }
+
+    match key_event.code {
+        KeyCode::Down => {
+            if let Some(next) = list.down() {
+                let message = Message::GetFollowedTags(Some(next));
+                app.backend_chan.as_ref().unwrap().send(message).await?;
+            }
+        }
+        KeyCode::Up => list.up(),
+        KeyCode::Enter => {
+            if let Some(tag) = list.selected() {
+                let timeline = Timeline::Tag(TagQuery::new(&tag.name));
+                app.pop_state();
+                app.switch_timeline(timeline).await?;
+            }
+        }
+        KeyCode::Char('/') => {
+            let tag = list.selected().map(|t| format!("#{} ", t.name));
+            list.input = Some(tag.unwrap_or_default());
+        }
+        KeyCode::Char('f') => {
+            if let Some(tag) = list.selected() {
+                let message = Message::FollowTag(tag.name.clone(), !tag.following);
+                app.backend_chan.as_ref().unwrap().send(message).await?;
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('q') => app.pop_state(),
+        _ => (),
+    }
+    Ok(())
+}
+
+async fn handle_lists(app: &mut App, event: Event) -> Result<()> {
+    let key_event = match event {
+        Event::Key(e) => e,
+        _ => return Ok(()),
+    };
+    let manager = match &mut app.state {
+        State::Lists(manager) => manager,
+        _ => unreachable!(),
+    };
+
+    if let Some(input) = &mut manager.input {
+        match key_event.code {
+            KeyCode::Enter => {
+                let message = match manager.input.take() {
+                    Some(ListInput::New(title)) if !title.trim().is_empty() => {
+                        Message::CreateList(title.trim().to_string())
+                    }
+                    Some(ListInput::Rename(id, title)) if !title.trim().is_empty() => {
+                        Message::RenameList(id, title.trim().to_string())
+                    }
+                    _ => return Ok(()),
+                };
+                app.backend_chan.as_ref().unwrap().send(message).await?;
+            }
+            KeyCode::Backspace => {
+                input.title().pop();
+            }
+            KeyCode::Char(c) => input.title().push(c),
+            KeyCode::Esc => manager.input = None,
+            _ => (),
+        }
+        return Ok(());
+    }
+
+    match key_event.code {
+        KeyCode::Down => manager.down(),
+        KeyCode::Up => manager.up(),
+        KeyCode::Char('n') => manager.input = Some(ListInput::New(String::new())),
+        KeyCode::Char('r') => {
+            if let Some(list) = manager.selected() {
+                manager.input = Some(ListInput::Rename(list.id.clone(), list.title.clone()));
+            }
+        }
+        KeyCode::Char('x') => {
+            if let Some(list) = manager.selected() {
+                let id = list.id.clone();
+                app.push_state(State::Confirm(ConfirmAction::DeleteList(id)));
+            }
+        }
+        KeyCode::Enter => {
+            if let Some(list) = manager.selected() {
+                match &manager.account {
+                    Some(account) => {
+                        let message =
+                            Message::EditListAccount(list.id.clone(), account.id.clone(), true);
+                        app.backend_chan.as_ref().unwrap().send(message).await?;
+                    }
+                    None => {
+                        let timeline = Timeline::List(list.clone());
+                        app.pop_state();
+                        app.switch_timeline(timeline).await?;
+                    }
+                }
+            }
+        }
+        KeyCode::Char('-') => {
+            if let Some(list) = manager.selected()
+                && let Some(account) = &manager.account
+            {
+                let message = Message::EditListAccount(list.id.clone(), account.id.clone(), false);
+                app.backend_chan.as_ref().unwrap().send(message).await?;
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('q') => app.pop_state(),
+        _ => (),
+    }
+    Ok(())
+}
+
+async fn handle_accounts(app: &mut App, event: Event) -> Result<()> {
+    let key_event = match event {
+        Event::Key(e) => e,
+        _ => return Ok(()),
+    };
+    let list = match &mut app.state {
+        State::Accounts(list) => list,
+        _ => unreachable!(),
+    };
+
+    match key_event.code {
+        KeyCode::Down => {
+            if let Some(next) = list.down() {
+                let message =
+                    Message::GetTweetAccounts(list.kind, list.tweet_id.clone(), Some(next));
+                app.backend_chan.as_ref().unwrap().send(message).await?;
+            }
+        }
+        KeyCode::Up => list.up(),
+        KeyCode::Enter => {
+            if let Some(account) = list.selected() {
+                let account = Box::new(account.clone());
+                app.push_state(State::Profile(account));
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('q') => app.pop_state(),
+        _ => (),
+    }
+    Ok(())
+}
+
+async fn handle_reaction_picker(app: &mut App, event: Event) -> Result<()> {
+    let key_event = match event {
+        Event::Key(e) => e,
+        _ => return Ok(()),
+    };
+    let picker = match &mut app.state {
+        State::ReactionPicker(picker) => picker,
+        _ => unreachable!(),
+    };
+
+    match key_event.code {
+        KeyCode::Down => picker.down(),
+        KeyCode::Up => picker.up(),
+        KeyCode::Backspace => picker.pop(),
+        KeyCode::Char(c) => picker.push(c),
+        KeyCode::Enter => {
+            if let Some(emoji) = picker.selected() {
+                let react = !picker.mine.contains(&emoji);
+                let message = Message::ReactTweet(picker.tweet_id.clone(), emoji, react);
+                app.pop_state();
+                app.backend_chan.as_ref().unwrap().send(message).await?;
+            }
+        }
+        KeyCode::Esc => app.pop_state(),
+        _ => (),
+    }
+    Ok(())
+}
+
+async fn handle_compose(app: &mut App, event: Event) -> Result<()> {
+    let compose = match &mut app.state {
+        State::Compose(c) => c,
+        _ => unreachable!(),
+    };
+    let key_event = match event {
+        Event::Key(e) => e,
+        _ => return Ok(()),
+    };
+    if compose.sending {
+        return Ok(());
+    }
+
+    if let Some(picker) = &mut compose.picker {
+        let res = match key_event.code {
+            KeyCode::Up => {
+                picker.up();
+                Ok(())
+            }
+            KeyCode::Down => {
+                picker.down();
+                Ok(())
+            }
+            KeyCode::Left | KeyCode::Backspace => picker.parent(),
+            KeyCode::Right | KeyCode::Enter => picker.select().map(|selected| {
+                if let Some(path) = selected {
+                    compose.picker = None;
+                    compose.attachments.push(MediaUpload::new(path));
+                    compose.focus = ComposeFocus::Description(compose.attachments.len() - 1);
+                }
+            }),
+            KeyCode::Esc => {
+                compose.picker = None;
+                Ok(())
+            }
+            _ => Ok(()),
+        };
+        if let Err(e) = res {
+            compose.error = Some(e.to_string());
+        }
+        return Ok(());
+    }
+
+    compose.error = None;
+    compose.dirty = true;
+    match (key_event.code, key_event.modifiers) {
+        (KeyCode::Esc, _) => {
+            app.save_draft();
+            app.pop_state();
+        }
+        (KeyCode::Char('o'), KeyModifiers::CONTROL) => {
+            let max = app
+                .instance_info
+                .as_ref()
+                .map(|i| i.max_media_attachments());
+            if max.is_some_and(|max| compose.attachment_count() >= max) {
+                compose.error = Some(format!("Can't attach more than {} files", max.unwrap()));
+            } else {
+                match FilePicker::new() {
+                    Ok(picker) => compose.picker = Some(picker),
+                    Err(e) => compose.error = Some(e.to_string()),
+                }
+            }
+        }
+        (KeyCode::Char('v'), KeyModifiers::CONTROL) => compose.cycle_visibility(),
+        (KeyCode::Char('t'), KeyModifiers::CONTROL) => {
+            let formats = app
+                .instance_info
+                .as_ref()
+                .map(|i| i.post_formats())
+                .unwrap_or(vec![ContentType::PlainText]);
+            compose.cycle_content_type(&formats);
+        }
+        (KeyCode::Char('p'), KeyModifiers::CONTROL) => compose.preview = !compose.preview,
+        (KeyCode::Char('x'), KeyModifiers::CONTROL) => compose.remove_focused_attachment(),
+        (KeyCode::Char('s'), KeyModifiers::CONTROL) => send_compose(app).await?,
+        (KeyCode::Tab, _) => {
+            let completion = compose
+                .emoji_query()
+                .and_then(|query| completions(query, &app.custom_emojis).first().copied());
+            match completion {
+                Some(emoji) => compose.complete_emoji(&emoji.shortcode),
+                None => compose.cycle_focus(),
+            }
+        }
+        (KeyCode::Enter, _) if compose.focus == ComposeFocus::Text => compose.text.push('\n'),
+        (KeyCode::Backspace, _) => {
+            compose.focused_text().map(String::pop);
+        }
+        (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+            if let Some(text) = compose.focused_text() {
+                text.push(c)
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+
+use crate::pleroma::{account::Account, list::List};
+
+/// Title being typed on the list screen
+#[derive(Clone)]
+pub enum ListInput {
+    New(String),
+    /// The id of the list and its new title
+    Rename(String, String),
+}
+
+impl ListInput {
+    pub fn title(&mut self) -> &mut String {
+        match self {
+            ListInput::New(title) | ListInput::Rename(_, title) => title,
+        }
+    }
+}
+
+/// Our lists, also used to pick the ones to add an account to
+#[derive(Clone, Default)]
+pub struct ListManager {
+    /// `None` until the server answers
+    pub lists: Option<Vec<List>>,
+    pub i: usize,
+    pub input: Option<ListInput>,
+    /// Author of the post the screen was opened from, to add to lists
+    pub account: Option<Box<Account>>,
+}
+
+impl ListManager {
+    pub fn for_account(account: Account) -> Self {
+        ListManager {
+            account: Some(Box::new(account)),
+            ..Default::default()
+        }
+    }
+
+    pub fn selected(&self) -> Option<&List> {
+        self.lists.as_ref()?.get(self.i)
+    }
+
+    pub fn down(&mut self) {
+        let len = self.lists.as_ref().map(|l| l.len()).unwrap_or(0);
+        if self.i + 1 < len {
+            self.i += 1;
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.i = self.i.saturating_sub(1);
+    }
+
+    /// Replaces a renamed list, adds a new one at the end
+    pub fn update(&mut self, list: List) {
+        let lists = self.lists.get_or_insert_default();
+        match lists.iter_mut().find(|l| l.id == list.id) {
+            Some(l) => *l = list,
+            None => lists.push(list),
+        }
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        if let Some(lists) = &mut self.lists {
+            lists.retain(|l| l.id != id);
+            self.i = self.i.min(lists.len().saturating_sub(1));
+        }
+    }
+}
+
+pub struct ListManagerWidget<'a> {
+    manager: &'a ListManager,
+}
+
+impl<'a> From<&'a ListManager> for ListManagerWidget<'a> {
+    fn from(value: &'a ListManager) -> Self {
+        ListManagerWidget { manager: value }
+    }
+}
+
+impl Widget for ListManagerWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let header = match (&self.manager.input, &self.manager.account) {
+            (Some(ListInput::New(title)), _) => format!("New list (Enter to create): {}", title),
+            (Some(ListInput::Rename(_, title)), _) => format!("Rename (Enter to save): {}", title),
+            (None, Some(account)) => format!(
+                "Lists for @{} | Add (Enter) | Remove (-) | Back (Esc)",
+                account.acct
+            ),
+            (None, None) => {
+                "Lists | Open (Enter) | New (n) | Rename (r) | Delete (x) | Back (Esc)".to_string()
+            }
+        };
+        buf.set_stringn(
+            area.left(),
+            area.top(),
+            header,
+            area.width as usize,
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+
+        let lists = match &self.manager.lists {
+            Some(lists) if lists.is_empty() => {
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1,
+                    "No lists yet",
+                    area.width as usize,
+                    Style::default().fg(Color::DarkGray),
+                );
+                return;
+            }
+            Some(lists) => lists,
+            None => {
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1,
+                    "Loading...",
+                    area.width as usize,
+                    Style::default().fg(Color::DarkGray),
+                );
+                return;
+            }
+        };
+
+        let rows = area.height.saturating_sub(1) as usize;
+        let skip = (self.manager.i + 1).saturating_sub(rows);
+        lists
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(rows)
+            .for_each(|(i, list)| {
+                let style = if i == self.manager.i {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1 + (i - skip) as u16,
+                    &list.title,
+                    area.width as usize,
+                    style,
+                );
+            });
+    }
+}
@@ -0,0 +1,201 @@
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+/// Renders Markdown as the server will format it, CommonMark with
+/// strikethrough. Mentions and hashtags get highlighted since the
+/// server turns them into links.
+pub fn render(text: &str) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut line: Vec<Span<'static>> = Vec::new();
+    let mut styles = vec![Style::default()];
+    // Next number of each nested list, `None` for bullet lists
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut quote = 0;
+    let mut code_block = false;
+
+    let flush = |line: &mut Vec<Span<'static>>, lines: &mut Vec<Line<'static>>| {
+        lines.push(Line::from(std::mem::take(line)));
+    };
+    let prefix = |quote: usize| {
+        Span::default()
+            .content("> ".repeat(quote))
+            .style(Style::default().fg(Color::DarkGray))
+    };
+
+    for event in Parser::new_ext(text, Options::ENABLE_STRIKETHROUGH) {
+        let style = *styles.last().unwrap();
+        match event {
+            Event::Start(tag) => {
+                let style = match tag {
+                    Tag::Heading { level, .. } => {
+                        let level = match level {
+                            HeadingLevel::H1 => 1,
+                            HeadingLevel::H2 => 2,
+                            HeadingLevel::H3 => 3,
+                            HeadingLevel::H4 => 4,
+                            HeadingLevel::H5 => 5,
+                            HeadingLevel::H6 => 6,
+                        };
+                        line.push(prefix(quote));
+                        line.push(Span::default().content(format!("{} ", "#".repeat(level))));
+                        style.add_modifier(Modifier::BOLD)
+                    }
+                    Tag::Paragraph => {
+                        if line.is_empty() {
+                            line.push(prefix(quote));
+                        }
+                        style
+                    }
+                    Tag::CodeBlock(_) => {
+                        code_block = true;
+                        line.push(prefix(quote));
+                        style.fg(Color::Cyan)
+                    }
+                    Tag::BlockQuote(_) => {
+                        quote += 1;
+                        style.fg(Color::DarkGray)
+                    }
+                    Tag::List(start) => {
+                        lists.push(start);
+                        style
+                    }
+                    Tag::Item => {
+                        if !line.is_empty() {
+                            flush(&mut line, &mut lines);
+                        }
+                        let depth = lists.len().saturating_sub(1);
+                        let bullet = match lists.last_mut() {
+                            Some(Some(n)) => {
+                                *n += 1;
+                                format!("{}. ", *n - 1)
+                            }
+                            _ => "\u{2022} ".to_string(),
+                        };
+                        line.push(prefix(quote));
+                        line.push(Span::default().content(format!(
+                            "{}{}",
+                            "  ".repeat(depth),
+                            bullet
+                        )));
+                        style
+                    }
+                    Tag::Emphasis => style.add_modifier(Modifier::ITALIC),
+                    Tag::Strong => style.add_modifier(Modifier::BOLD),
+                    Tag::Strikethrough => style.add_modifier(Modifier::CROSSED_OUT),
+                    Tag::Link { .. } => style.fg(Color::Blue).add_modifier(Modifier::UNDERLINED),
+                    _ => style,
+                };
+                styles.push(style);
+            }
+            Event::End(tag) => {
+                styles.pop();
+                match tag {
+                    TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::CodeBlock => {
+                        code_block = false;
+                        flush(&mut line, &mut lines);
+                        // Blank line between blocks, except inside lists
+                        if lists.is_empty() {
+                            lines.push(Line::default());
+                        }
+                    }
+                    TagEnd::BlockQuote(_) => quote -= 1,
+                    TagEnd::List(_) => {
+                        lists.pop();
+                        if !line.is_empty() {
+                            flush(&mut line, &mut lines);
+                        }
+                        if lists.is_empty() {
+                            lines.push(Line::default());
+                        }
+                    }
+                    TagEnd::Item if !line.is_empty() => flush(&mut line, &mut lines),
+                    _ => (),
+                }
+            }
+            Event::Text(text) if code_block => {
+                for (i, l) in text.lines().enumerate() {
+                    if i > 0 {
+                        flush(&mut line, &mut lines);
+                        line.push(prefix(quote));
+                    }
+                    line.push(Span::default().content(l.to_string()).style(style));
+                }
+            }
+            Event::Text(text) => line.extend(highlight(&text, style)),
+            Event::Code(code) => line.push(
+                Span::default()
+                    .content(code.to_string())
+                    .style(style.fg(Color::Cyan)),
+            ),
+            Event::SoftBreak => line.push(Span::default().content(" ")),
+            Event::HardBreak => {
+                flush(&mut line, &mut lines);
+                line.push(prefix(quote));
+            }
+            Event::Rule => {
+                lines.push(Line::from("\u{2500}".repeat(8)));
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                line.push(Span::default().content(html.to_string()).style(style))
+            }
+            _ => (),
+        }
+    }
+    if !line.is_empty() {
+        flush(&mut line, &mut lines);
+    }
+    while lines.last().is_some_and(|l| l.spans.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Splits out the words the server turns into mention and hashtag links
+fn highlight(text: &str, style: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut word_start = 0;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        if !c.is_whitespace() {
+            continue;
+        }
+        let word = &text[word_start..i];
+        let word_style = if word.len() > 1 && (word.starts_with('@') || word.starts_with('#')) {
+            style.fg(Color::Blue)
+        } else {
+            style
+        };
+        if !word.is_empty() {
+            spans.push(Span::default().content(word.to_string()).style(word_style));
+        }
+        if i < text.len() {
+            spans.push(Span::default().content(c.to_string()).style(style));
+        }
+        word_start = i + c.len_utf8();
+    }
+    spans
+}
+
+#[cfg(test)]
+mod test {
+    use super::render;
+
+    #[test]
+    fn markdown_blocks() {
+        let lines = render("# Title\n\nsome **bold** text\n\n- one\n- two");
+        let text = lines.iter().map(|l| l.to_string()).collect::<Vec<String>>();
+        assert_eq!(
+            text,
+            vec![
+                "# Title",
+                "",
+                "some bold text",
+                "",
+                "\u{2022} one",
+                "\u{2022} two"
+            ]
+        );
+    }
+}
@@ -1,7 +1,22 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
+use image::RgbaImage;
 use ratatui::crossterm::event::Event;
 
-use crate::pleroma::tweet::Tweet;
+use super::{accounts::AccountListKind, download::DownloadProgress, thread::Thread};
+use crate::pleroma::{
+    account::Account,
+    chat::{Chat, ChatMessage},
+    conversation::Conversation,
+    emoji::CustomEmoji,
+    instance::Instance,
+    list::List,
+    media::MediaUpload,
+    scheduled::ScheduledTweet,
+    tag::{Hashtag, TagQuery},
+    tweet::{NewTweet, Tweet, TweetRevision, TweetSource},
+};
 
 pub enum Message {
     GetHomeTimeline(Option<String>),
@@ -10,6 +25,110 @@ pub enum Message {
     GetPublicTimelineResponse(Result<Vec<Tweet>>),
     GetLocalTimeline(Option<String>),
     GetLocalTimelineResponse(Result<Vec<Tweet>>),
+    /// Page of the list's timeline from the given cursor
+    GetListTimeline(String, Option<String>),
+    GetListTimelineResponse(String, Result<(Vec<Tweet>, Option<String>)>),
+    GetTagTimeline(TagQuery, Option<String>),
+    GetTagTimelineResponse(TagQuery, Result<(Vec<Tweet>, Option<String>)>),
+    /// Page of bookmarks from the given cursor
+    GetBookmarks(Option<String>),
+    GetBookmarksResponse(Result<(Vec<Tweet>, Option<String>)>),
+    /// Bookmarks or unbookmarks the post
+    BookmarkTweet(String, bool),
+    BookmarkTweetResponse(Result<Box<Tweet>>),
+    GetFavourites(Option<String>),
+    GetFavouritesResponse(Result<(Vec<Tweet>, Option<String>)>),
+    /// Page of the accounts that favourited or boosted the post
+    GetTweetAccounts(AccountListKind, String, Option<String>),
+    /// With the kind and post asked for, the list may be gone by then
+    GetTweetAccountsResponse(
+        AccountListKind,
+        String,
+        Result<(Vec<Account>, Option<String>)>,
+    ),
+    GetCustomEmojis,
+    GetCustomEmojisResponse(Result<Vec<CustomEmoji>>),
+    /// Adds or removes our reaction with the emoji on the post
+    ReactTweet(String, String, bool),
+    ReactTweetResponse(Result<Box<Tweet>>),
+    GetOwnAccount,
+    GetOwnAccountResponse(Result<Account>),
+    /// Deletes the post, the flag tells whether to redraft it
+    DeleteTweet(String, bool),
+    DeleteTweetResponse(Result<Box<Tweet>>, bool),
+    GetInstance,
+    GetInstanceResponse(Result<Instance>),
+    PostTweet(NewTweet, Vec<MediaUpload>),
+    PostTweetResponse(Result<()>),
+    GetScheduledTweets,
+    GetScheduledTweetsResponse(Result<Vec<ScheduledTweet>>),
+    /// Moves the scheduled post to the given time
+    RescheduleTweet(String, String),
+    RescheduleTweetResponse(Result<ScheduledTweet>),
+    CancelScheduledTweet(String),
+    CancelScheduledTweetResponse(String, Result<()>),
+    /// Replaces the content of the post with the given id
+    EditTweet(String, NewTweet, Vec<MediaUpload>),
+    EditTweetResponse(Result<Box<Tweet>>),
+    GetTweetSource(String),
+    GetTweetSourceResponse(Result<TweetSource>),
+    GetTweetHistory(String),
+    GetTweetHistoryResponse(Result<Vec<TweetRevision>>),
+    /// Page of direct message threads from the given cursor
+    GetConversations(Option<String>),
+    GetConversationsResponse(Result<(Vec<Conversation>, Option<String>)>),
+    ReadConversation(String),
+    ReadConversationResponse(Result<Box<Conversation>>),
+    RemoveConversation(String),
+    RemoveConversationResponse(String, Result<()>),
+    GetLists,
+    GetListsResponse(Result<Vec<List>>),
+    CreateList(String),
+    CreateListResponse(Result<List>),
+    /// Gives the list a new title
+    RenameList(String, String),
+    RenameListResponse(Result<List>),
+    DeleteList(String),
+    DeleteListResponse(String, Result<()>),
+    /// Adds the account to the list, or removes it
+    EditListAccount(String, String, bool),
+    EditListAccountResponse(Result<()>, bool),
+    GetFollowedTags(Option<String>),
+    GetFollowedTagsResponse(Result<(Vec<Hashtag>, Option<String>)>),
+    /// All of them at once, to pin their timelines
+    GetAllFollowedTags,
+    GetAllFollowedTagsResponse(Result<Vec<Hashtag>>),
+    /// Follows or unfollows the hashtag
+    FollowTag(String, bool),
+    FollowTagResponse(Result<Hashtag>),
+    GetChats,
+    GetChatsResponse(Result<Vec<Chat>>),
+    /// Opens the chat with the account
+    OpenChat(String),
+    OpenChatResponse(Result<Box<Chat>>),
+    /// Page of messages of the chat from the given cursor
+    GetChatMessages(String, Option<String>),
+    GetChatMessagesResponse(String, Result<(Vec<ChatMessage>, Option<String>)>),
+    /// Sends the text and the attachment to the chat
+    SendToChat(String, String, Option<MediaUpload>),
+    SendToChatResponse(String, Result<Box<ChatMessage>>),
+    /// Marks the chat as read up to the given message
+    ReadChat(String, String),
+    ReadChatResponse(Result<Box<Chat>>),
+    /// A chat got a new message, from the user stream
+    ChatUpdate(Box<Chat>),
+    /// A post and its context
+    GetThread(String),
+    GetThreadResponse(Result<Thread>),
+    /// Downloads the thumbnails at the given `preview_url`s
+    GetPreviews(Vec<String>),
+    GetPreviewResponse(String, Result<RgbaImage>),
+    /// Saves the attachments of a post, or of its whole thread,
+    /// into the directory
+    DownloadMedia(String, bool, PathBuf),
+    DownloadProgress(DownloadProgress),
+    /// Number of files downloaded
+    DownloadMediaResponse(Result<usize>),
     Tick,
     Input(Event),
 }
@@ -1,16 +1,67 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
 use anyhow::{Result, anyhow};
-use cli_log::info;
+use cli_log::{info, warn};
 use input::handle_input;
 use message::Message;
 use state::{State, Timeline};
 use tokio::sync::mpsc::{Receiver, Sender, channel};
 
-use crate::{pleroma::tweet::Tweet, renderer::terminal::Terminal};
+use crate::{
+    config::Config,
+    pleroma::{
+        account::Account,
+        emoji::CustomEmoji,
+        instance::Instance,
+        list::List,
+        tag::TagQuery,
+        tweet::{MediaAttatchment, Tweet},
+    },
+    renderer::{image::GraphicsProtocol, terminal::Terminal},
+};
+use chats::{ChatList, ChatWindow};
+use compose::Compose;
+use content_warning::Reveals;
+use conversations::ConversationList;
+use drafts::Drafts;
+use hashtags::HashtagList;
+use lists::ListManager;
+use preview::{Preview, Previews};
+use status::Status;
+
+/// Decoded thumbnails and emoji kept in memory
+const PREVIEW_MEMORY: usize = 256;
+/// Redraws this often even without news, for post ages and poll countdowns
+const CLOCK_REFRESH: Duration = Duration::from_secs(1);
 
+mod accounts;
 pub mod backend;
+mod chats;
+mod compose;
+mod confirm;
+mod content_warning;
+mod conversations;
+mod diff;
+mod download;
+mod drafts;
+mod emoji;
+mod file_picker;
+mod hashtags;
 pub mod input;
+mod lists;
+mod markdown;
 pub mod message;
+mod open;
+mod preview;
+mod reactions;
+mod scheduled;
 mod state;
+mod status;
+mod thread;
+mod time;
 mod timeline;
 
 #[derive(Default)]
@@ -20,40 +71,232 @@ struct Timelines {
     local: Vec<Tweet>,
     // Everywhere
     public: Vec<Tweet>,
+    bookmarks: Vec<Tweet>,
+    /// Cursor of the next page of bookmarks, `None` once all are loaded
+    bookmarks_next: Option<String>,
+    favourites: Vec<Tweet>,
+    favourites_next: Option<String>,
+    /// Opened lists by id, with the cursor of their next page
+    lists: HashMap<String, (Vec<Tweet>, Option<String>)>,
+    tags: HashMap<TagQuery, (Vec<Tweet>, Option<String>)>,
+    /// Timelines whose first page was asked for, so that switching back
+    /// before it arrives doesn't fetch it twice
+    requested: Vec<Timeline>,
+}
+
+/// Swaps in the new version of a post wherever it or a boost of it is
+fn replace(tweets: &mut [Tweet], tweet: &Tweet) {
+    for t in tweets {
+        if t.id == tweet.id {
+            *t = tweet.clone();
+        } else if let Some(reblog) = &mut t.reblog
+            && reblog.id == tweet.id
+        {
+            **reblog = tweet.clone();
+        }
+    }
+}
+
+/// What a list or a hashtag shows before it's fetched
+static NO_TWEETS: Vec<Tweet> = Vec::new();
+
+impl Timelines {
+    fn get(&self, timeline: &Timeline) -> &Vec<Tweet> {
+        match timeline {
+            Timeline::Home => &self.home,
+            Timeline::Local => &self.local,
+            Timeline::Public => &self.public,
+            Timeline::Bookmarks => &self.bookmarks,
+            Timeline::Favourites => &self.favourites,
+            Timeline::List(list) => self
+                .lists
+                .get(&list.id)
+                .map(|(tweets, _)| tweets)
+                .unwrap_or(&NO_TWEETS),
+            Timeline::Tag(query) => self
+                .tags
+                .get(query)
+                .map(|(tweets, _)| tweets)
+                .unwrap_or(&NO_TWEETS),
+        }
+    }
+
+    /// Marks the first page as asked for, false if it already was
+    fn request(&mut self, timeline: &Timeline) -> bool {
+        if self.requested.iter().any(|t| t.is(timeline)) {
+            return false;
+        }
+        self.requested.push(timeline.clone());
+        true
+    }
+
+    /// Lets the first page be asked for again, after it failed or the
+    /// posts got dropped
+    fn forget(&mut self, forgotten: impl Fn(&Timeline) -> bool) {
+        self.requested.retain(|t| !forgotten(t));
+    }
+
+    fn all_mut(&mut self) -> impl Iterator<Item = &mut Vec<Tweet>> {
+        [
+            &mut self.home,
+            &mut self.local,
+            &mut self.public,
+            &mut self.bookmarks,
+            &mut self.favourites,
+        ]
+        .into_iter()
+        .chain(self.lists.values_mut().map(|(tweets, _)| tweets))
+        .chain(self.tags.values_mut().map(|(tweets, _)| tweets))
+    }
+
+    /// Request for the next page of the timelines paged by cursor, taking
+    /// the cursor so that it's only requested once
+    fn next_page(&mut self, timeline: &Timeline) -> Option<Message> {
+        match timeline {
+            Timeline::Bookmarks => Some(Message::GetBookmarks(Some(self.bookmarks_next.take()?))),
+            Timeline::Favourites => {
+                Some(Message::GetFavourites(Some(self.favourites_next.take()?)))
+            }
+            Timeline::List(list) => {
+                let (_, next) = self.lists.get_mut(&list.id)?;
+                Some(Message::GetListTimeline(
+                    list.id.clone(),
+                    Some(next.take()?),
+                ))
+            }
+            Timeline::Tag(query) => {
+                let (_, next) = self.tags.get_mut(query)?;
+                Some(Message::GetTagTimeline(query.clone(), Some(next.take()?)))
+            }
+            _ => None,
+        }
+    }
+
+    fn find(&self, id: &str) -> Option<&Tweet> {
+        [
+            &self.home,
+            &self.local,
+            &self.public,
+            &self.bookmarks,
+            &self.favourites,
+        ]
+        .into_iter()
+        .chain(self.lists.values().map(|(tweets, _)| tweets))
+        .chain(self.tags.values().map(|(tweets, _)| tweets))
+        .flatten()
+        .flat_map(|t| [Some(t), t.reblog.as_deref()])
+        .flatten()
+        .find(|t| t.id == id)
+    }
+
+    /// Swaps in the new version of an edited post, boosts included
+    fn replace(&mut self, tweet: &Tweet) {
+        for timeline in self.all_mut() {
+            replace(timeline, tweet);
+        }
+    }
+
+    /// Drops the post, and the boosts of it, from every timeline
+    fn remove(&mut self, id: &str) {
+        for timeline in self.all_mut() {
+            timeline.retain(|t| t.id != id && t.reblog.as_ref().is_none_or(|r| r.id != id));
+        }
+    }
 }
 
 pub struct App {
     timelines: Timelines,
+    /// The built-in timelines, then the opened ones
+    tabs: Vec<Timeline>,
+    /// Names of the hashtags we follow, their timelines are pinned
+    followed_tags: Vec<String>,
     state: State,
+    /// Screens to go back to
+    history: Vec<State>,
     backend_chan: Option<Sender<Message>>,
     pub recv_end: Receiver<Message>,
     pub send_end: Sender<Message>,
     terminal: Terminal,
     instance: String,
+    /// The logged in account
+    me: Option<Account>,
+    instance_info: Option<Instance>,
+    custom_emojis: Vec<CustomEmoji>,
+    /// Thumbnails by `preview_url`
+    previews: Previews,
+    graphics: GraphicsProtocol,
+    config: Config,
+    status: Option<Status>,
+    /// CWs and sensitive media toggled by the user
+    reveals: Reveals,
+    drafts: Drafts,
     should_render: bool,
+    rendered_at: Instant,
 }
 
 impl App {
-    pub async fn new(instance: &str) -> Result<Self> {
+    pub async fn new(instance: &str, config: Config) -> Result<Self> {
         let (send_end, recv_end) = channel(10);
+        let (drafts, status) = match Drafts::load() {
+            Ok(drafts) => (drafts, None),
+            // Drafts won't be saved, better to say so now than on a crash
+            Err(e) => (
+                Drafts::default(),
+                Some(Status::Error(format!("Couldn't load drafts: {}", e))),
+            ),
+        };
         Ok(App {
             timelines: Timelines::default(),
+            tabs: Timeline::BUILT_IN.to_vec(),
+            followed_tags: Vec::new(),
             state: State::Timeline(Timeline::Home, 0),
+            history: Vec::new(),
             backend_chan: None,
             recv_end,
             send_end,
             terminal: Terminal::new()?,
             instance: instance.to_string(),
+            me: None,
+            instance_info: None,
+            custom_emojis: Vec::new(),
+            previews: Previews::new(PREVIEW_MEMORY),
+            graphics: GraphicsProtocol::detect(),
+            config,
+            status,
+            reveals: Reveals::default(),
+            drafts,
             should_render: true,
+            rendered_at: Instant::now(),
         })
     }
 
     pub async fn start(&mut self) -> Result<()> {
+        self.timelines.request(&Timeline::Home);
         self.backend_chan
             .as_ref()
             .unwrap()
             .send(Message::GetHomeTimeline(None))
             .await?;
+        self.backend_chan
+            .as_ref()
+            .unwrap()
+            .send(Message::GetInstance)
+            .await?;
+        self.backend_chan
+            .as_ref()
+            .unwrap()
+            .send(Message::GetOwnAccount)
+            .await?;
+        self.backend_chan
+            .as_ref()
+            .unwrap()
+            .send(Message::GetCustomEmojis)
+            .await?;
+        self.backend_chan
+            .as_ref()
+            .unwrap()
+            .send(Message::GetAllFollowedTags)
+            .await?;
 
         while !self.recv_end.is_closed() {
             if let Some(m) = self.recv_end.recv().await {
@@ -63,21 +306,546 @@ impl App {
                 };
                 match m {
                     Message::GetHomeTimelineResponse(res) => match res {
-                        Ok(data) => self.timelines.home.extend(data),
-                        // TODO Display error on the frontend
-                        Err(_) => todo!(),
+                        Ok(data) => {
+                            self.request_previews(&data).await?;
+                            self.timelines.home.extend(data);
+                        }
+                        Err(e) => {
+                            self.timelines.forget(|t| matches!(t, Timeline::Home));
+                            self.status = Some(Status::Error(format!(
+                                "Couldn't fetch the home timeline: {}",
+                                e
+                            )))
+                        }
                     },
                     Message::GetPublicTimelineResponse(res) => match res {
-                        Ok(data) => self.timelines.public.extend(data),
-                        Err(_) => todo!(),
+                        Ok(data) => {
+                            self.request_previews(&data).await?;
+                            self.timelines.public.extend(data);
+                        }
+                        Err(e) => {
+                            self.timelines.forget(|t| matches!(t, Timeline::Public));
+                            self.status = Some(Status::Error(format!(
+                                "Couldn't fetch the public timeline: {}",
+                                e
+                            )))
+                        }
                     },
                     Message::GetLocalTimelineResponse(res) => match res {
-                        Ok(data) => self.timelines.local.extend(data),
-                        Err(_) => todo!(),
+                        Ok(data) => {
+                            self.request_previews(&data).await?;
+                            self.timelines.local.extend(data);
+                        }
+                        Err(e) => {
+                            self.timelines.forget(|t| matches!(t, Timeline::Local));
+                            self.status = Some(Status::Error(format!(
+                                "Couldn't fetch the local timeline: {}",
+                                e
+                            )))
+                        }
+                    },
+                    Message::GetListTimelineResponse(id, res) => match res {
+                        Ok((data, next)) => {
+                            self.request_previews(&data).await?;
+                            let (tweets, cursor) = self.timelines.lists.entry(id).or_default();
+                            tweets.extend(data);
+                            *cursor = next;
+                        }
+                        Err(e) => {
+                            self.timelines
+                                .forget(|t| matches!(t, Timeline::List(l) if l.id == id));
+                            self.status =
+                                Some(Status::Error(format!("Couldn't fetch the list: {}", e)))
+                        }
+                    },
+                    Message::GetTagTimelineResponse(query, res) => match res {
+                        Ok((data, next)) => {
+                            self.request_previews(&data).await?;
+                            let (tweets, cursor) = self.timelines.tags.entry(query).or_default();
+                            tweets.extend(data);
+                            *cursor = next;
+                        }
+                        Err(e) => {
+                            self.timelines
+                                .forget(|t| matches!(t, Timeline::Tag(q) if *q == query));
+                            self.status =
+                                Some(Status::Error(format!("Couldn't fetch the hashtag: {}", e)))
+                        }
+                    },
+                    Message::GetBookmarksResponse(res) => match res {
+                        Ok((data, next)) => {
+                            self.request_previews(&data).await?;
+                            self.timelines.bookmarks.extend(data);
+                            self.timelines.bookmarks_next = next;
+                        }
+                        Err(e) => {
+                            self.timelines.forget(|t| matches!(t, Timeline::Bookmarks));
+                            self.status =
+                                Some(Status::Error(format!("Couldn't fetch bookmarks: {}", e)))
+                        }
+                    },
+                    Message::GetFavouritesResponse(res) => match res {
+                        Ok((data, next)) => {
+                            self.request_previews(&data).await?;
+                            self.timelines.favourites.extend(data);
+                            self.timelines.favourites_next = next;
+                        }
+                        Err(e) => {
+                            self.timelines.forget(|t| matches!(t, Timeline::Favourites));
+                            self.status =
+                                Some(Status::Error(format!("Couldn't fetch favourites: {}", e)))
+                        }
+                    },
+                    Message::GetTweetAccountsResponse(kind, id, res) => match res {
+                        Ok((data, next)) => {
+                            if let State::Accounts(list) = &mut self.state
+                                && list.kind == kind
+                                && list.tweet_id == id
+                            {
+                                list.accounts.get_or_insert_default().extend(data);
+                                list.next = next;
+                            }
+                        }
+                        Err(e) => {
+                            self.status =
+                                Some(Status::Error(format!("Couldn't fetch accounts: {}", e)))
+                        }
+                    },
+                    Message::BookmarkTweetResponse(res) => match res {
+                        Ok(tweet) => {
+                            self.replace_tweet(&tweet);
+                            if !tweet.bookmarked {
+                                self.timelines.bookmarks.retain(|t| t.id != tweet.id);
+                                self.clamp_selection();
+                            } else if !self.timelines.bookmarks.is_empty() {
+                                self.timelines.bookmarks.insert(0, *tweet);
+                            }
+                        }
+                        Err(e) => {
+                            self.status = Some(Status::Error(format!("Couldn't bookmark: {}", e)))
+                        }
+                    },
+                    Message::GetCustomEmojisResponse(res) => match res {
+                        Ok(data) => self.custom_emojis = data,
+                        // Only offered in the pickers
+                        Err(e) => warn!("Couldn't fetch custom emoji: {}", e),
+                    },
+                    Message::ReactTweetResponse(res) => match res {
+                        Ok(tweet) => self.replace_tweet(&tweet),
+                        Err(e) => {
+                            self.status = Some(Status::Error(format!("Couldn't react: {}", e)))
+                        }
+                    },
+                    Message::GetInstanceResponse(res) => match res {
+                        Ok(data) => self.instance_info = Some(data),
+                        // Only used to check limits early, the server checks them anyway
+                        Err(e) => warn!("Couldn't fetch instance info: {}", e),
+                    },
+                    Message::GetPreviewResponse(url, res) => {
+                        let preview = match res {
+                            Ok(image) => Preview::Ready(image),
+                            Err(e) => {
+                                warn!("Couldn't load preview {}: {}", url, e);
+                                Preview::Failed
+                            }
+                        };
+                        self.previews.insert(url, preview);
+                    }
+                    Message::PostTweetResponse(res) => {
+                        if let State::Compose(compose) = &mut self.state {
+                            match res {
+                                Ok(_) => {
+                                    if let Some(id) = compose.draft_id {
+                                        self.drafts.remove(id);
+                                        if let Err(e) = self.drafts.save() {
+                                            warn!("Couldn't save drafts: {}", e);
+                                        }
+                                    }
+                                    if !compose.scheduled_at.trim().is_empty() {
+                                        self.status = Some(Status::Info(format!(
+                                            "Post scheduled for {}",
+                                            compose.scheduled_at.trim()
+                                        )));
+                                    }
+                                    self.pop_state();
+                                }
+                                Err(e) => {
+                                    compose.sending = false;
+                                    compose.error = Some(e.to_string());
+                                }
+                            }
+                        }
+                    }
+                    Message::GetScheduledTweetsResponse(res) => match res {
+                        Ok(tweets) => {
+                            if let State::Scheduled(list) = &mut self.state {
+                                list.tweets = Some(tweets);
+                                list.i = 0;
+                            }
+                        }
+                        Err(e) => {
+                            self.status = Some(Status::Error(format!(
+                                "Couldn't fetch scheduled posts: {}",
+                                e
+                            )))
+                        }
+                    },
+                    Message::RescheduleTweetResponse(res) => match res {
+                        Ok(tweet) => {
+                            self.status = Some(Status::Info(format!(
+                                "Rescheduled for {}",
+                                tweet.scheduled_at
+                            )));
+                            if let State::Scheduled(list) = &mut self.state {
+                                list.update(tweet);
+                            }
+                        }
+                        Err(e) => {
+                            self.status = Some(Status::Error(format!("Couldn't reschedule: {}", e)))
+                        }
+                    },
+                    Message::CancelScheduledTweetResponse(id, res) => match res {
+                        Ok(()) => {
+                            self.status =
+                                Some(Status::Info("Scheduled post cancelled".to_string()));
+                            if let State::Scheduled(list) = &mut self.state {
+                                list.remove(&id);
+                            }
+                        }
+                        Err(e) => {
+                            self.status = Some(Status::Error(format!("Couldn't cancel: {}", e)))
+                        }
+                    },
+                    Message::EditTweetResponse(res) => {
+                        if let State::Compose(compose) = &mut self.state {
+                            match res {
+                                Ok(tweet) => {
+                                    self.replace_tweet(&tweet);
+                                    self.pop_state();
+                                }
+                                Err(e) => {
+                                    compose.sending = false;
+                                    compose.error = Some(e.to_string());
+                                }
+                            }
+                        }
+                    }
+                    Message::GetTweetSourceResponse(res) => match res {
+                        Ok(source) => match self.timelines.find(&source.id) {
+                            Some(tweet) => {
+                                let compose = Compose::edit(tweet, source);
+                                self.push_state(State::Compose(compose));
+                            }
+                            None => warn!("Got the source of {}, not in any timeline", source.id),
+                        },
+                        Err(e) => {
+                            self.status =
+                                Some(Status::Error(format!("Couldn't fetch the source: {}", e)))
+                        }
+                    },
+                    Message::GetTweetHistoryResponse(res) => match res {
+                        Ok(revisions) => {
+                            let last = revisions.len().saturating_sub(1);
+                            self.push_state(State::History(revisions, last));
+                        }
+                        Err(e) => {
+                            self.status =
+                                Some(Status::Error(format!("Couldn't fetch the history: {}", e)))
+                        }
+                    },
+                    Message::GetConversationsResponse(res) => match res {
+                        Ok((data, next)) => {
+                            if let Some(list) = self.conversations() {
+                                list.conversations.get_or_insert_default().extend(data);
+                                list.next = next;
+                            }
+                        }
+                        Err(e) => {
+                            self.status = Some(Status::Error(format!(
+                                "Couldn't fetch conversations: {}",
+                                e
+                            )))
+                        }
+                    },
+                    Message::ReadConversationResponse(res) => match res {
+                        Ok(conversation) => {
+                            if let Some(list) = self.conversations() {
+                                list.update(*conversation);
+                            }
+                        }
+                        Err(e) => {
+                            self.status = Some(Status::Error(format!(
+                                "Couldn't mark the conversation as read: {}",
+                                e
+                            )))
+                        }
+                    },
+                    Message::RemoveConversationResponse(id, res) => match res {
+                        Ok(()) => {
+                            if let Some(list) = self.conversations() {
+                                list.remove(&id);
+                            }
+                        }
+                        Err(e) => {
+                            self.status = Some(Status::Error(format!(
+                                "Couldn't remove the conversation: {}",
+                                e
+                            )))
+                        }
+                    },
+                    Message::GetListsResponse(res) => match res {
+                        Ok(data) => {
+                            if let Some(manager) = self.list_manager() {
+                                manager.lists = Some(data);
+                                manager.i = 0;
+                            }
+                        }
+                        Err(e) => {
+                            self.status =
+                                Some(Status::Error(format!("Couldn't fetch lists: {}", e)))
+                        }
+                    },
+                    Message::CreateListResponse(res) => match res {
+                        Ok(list) => {
+                            if let Some(manager) = self.list_manager() {
+                                manager.update(list);
+                            }
+                        }
+                        Err(e) => {
+                            self.status =
+                                Some(Status::Error(format!("Couldn't create the list: {}", e)))
+                        }
+                    },
+                    Message::RenameListResponse(res) => match res {
+                        Ok(list) => {
+                            self.retitle_list(&list);
+                            if let Some(manager) = self.list_manager() {
+                                manager.update(list);
+                            }
+                        }
+                        Err(e) => {
+                            self.status =
+                                Some(Status::Error(format!("Couldn't rename the list: {}", e)))
+                        }
+                    },
+                    Message::DeleteListResponse(id, res) => match res {
+                        Ok(()) => {
+                            self.close_list(&id);
+                            if let Some(manager) = self.list_manager() {
+                                manager.remove(&id);
+                            }
+                        }
+                        Err(e) => {
+                            self.status =
+                                Some(Status::Error(format!("Couldn't delete the list: {}", e)))
+                        }
+                    },
+                    Message::EditListAccountResponse(res, add) => {
+                        self.status = Some(match res {
+                            Ok(()) if add => Status::Info("Added to the list".to_string()),
+                            Ok(()) => Status::Info("Removed from the list".to_string()),
+                            Err(e) => Status::Error(format!("Couldn't edit the list: {}", e)),
+                        });
+                    }
+                    Message::GetFollowedTagsResponse(res) => match res {
+                        Ok((data, next)) => {
+                            data.iter().for_each(|tag| self.pin_tag(&tag.name));
+                            if let Some(list) = self.hashtag_list()
+                                && list.followed
+                            {
+                                list.tags.get_or_insert_default().extend(data);
+                                list.next = next;
+                            }
+                        }
+                        Err(e) => {
+                            self.status = Some(Status::Error(format!(
+                                "Couldn't fetch followed hashtags: {}",
+                                e
+                            )))
+                        }
+                    },
+                    Message::GetAllFollowedTagsResponse(res) => match res {
+                        Ok(data) => data.iter().for_each(|tag| self.pin_tag(&tag.name)),
+                        Err(e) => {
+                            self.status = Some(Status::Error(format!(
+                                "Couldn't fetch followed hashtags: {}",
+                                e
+                            )))
+                        }
+                    },
+                    Message::FollowTagResponse(res) => match res {
+                        Ok(tag) => {
+                            self.status = Some(Status::Info(if tag.following {
+                                self.pin_tag(&tag.name);
+                                format!("Following #{}", tag.name)
+                            } else {
+                                self.unpin_tag(&tag.name);
+                                format!("Unfollowed #{}", tag.name)
+                            }));
+                            if let Some(list) = self.hashtag_list() {
+                                list.update(tag);
+                            }
+                        }
+                        Err(e) => {
+                            self.status =
+                                Some(Status::Error(format!("Couldn't follow the hashtag: {}", e)))
+                        }
+                    },
+                    Message::GetChatsResponse(res) => match res {
+                        Ok(data) => {
+                            if let Some(list) = self.chats() {
+                                list.chats = Some(data);
+                                list.i = 0;
+                            }
+                        }
+                        Err(e) => {
+                            self.status =
+                                Some(Status::Error(format!("Couldn't fetch chats: {}", e)))
+                        }
+                    },
+                    Message::OpenChatResponse(res) => match res {
+                        Ok(chat) => {
+                            let message = Message::GetChatMessages(chat.id.clone(), None);
+                            self.push_state(State::Chat(Box::new(ChatWindow::new(*chat))));
+                            self.backend_chan.as_ref().unwrap().send(message).await?;
+                        }
+                        Err(e) => {
+                            self.status =
+                                Some(Status::Error(format!("Couldn't open the chat: {}", e)))
+                        }
+                    },
+                    Message::GetChatMessagesResponse(chat_id, res) => match res {
+                        Ok((data, next)) => {
+                            if let Some(window) = self.chat_window(&chat_id) {
+                                window.extend(data);
+                                window.next = next;
+                            }
+                            self.read_chat(&chat_id).await?;
+                        }
+                        Err(e) => {
+                            self.status =
+                                Some(Status::Error(format!("Couldn't fetch messages: {}", e)))
+                        }
+                    },
+                    Message::SendToChatResponse(chat_id, res) => match self.chat_window(&chat_id) {
+                        Some(window) => {
+                            window.sending = false;
+                            match res {
+                                Ok(message) => {
+                                    window.input.clear();
+                                    window.attachment = None;
+                                    window.scroll = 0;
+                                    window.push(*message);
+                                }
+                                Err(e) => window.error = Some(e.to_string()),
+                            }
+                        }
+                        // Left the chat while sending
+                        None => {
+                            if let Err(e) = res {
+                                self.status = Some(Status::Error(format!(
+                                    "Couldn't send the chat message: {}",
+                                    e
+                                )))
+                            }
+                        }
+                    },
+                    Message::ReadChatResponse(res) => match res {
+                        Ok(chat) => {
+                            if let Some(window) = self.chat_window(&chat.id) {
+                                window.chat = (*chat).clone();
+                            }
+                            if let Some(list) = self.chats() {
+                                list.update(*chat);
+                            }
+                        }
+                        Err(e) => {
+                            self.status = Some(Status::Error(format!(
+                                "Couldn't mark the chat as read: {}",
+                                e
+                            )))
+                        }
+                    },
+                    Message::ChatUpdate(chat) => {
+                        let viewing = match &mut self.state {
+                            State::Chat(window) if window.chat.id == chat.id => {
+                                if let Some(message) = &chat.last_message {
+                                    window.push(message.clone());
+                                }
+                                window.chat = (*chat).clone();
+                                true
+                            }
+                            _ => false,
+                        };
+                        let incoming = chat
+                            .last_message
+                            .as_ref()
+                            .is_some_and(|m| m.account_id == chat.account.id);
+                        if viewing {
+                            self.read_chat(&chat.id).await?;
+                        } else if incoming {
+                            self.status = Some(Status::Info(format!(
+                                "New chat message from @{}",
+                                chat.account.acct
+                            )));
+                        }
+                        if let Some(list) = self.chats() {
+                            list.update(*chat);
+                        }
+                    }
+                    Message::GetThreadResponse(res) => match res {
+                        Ok(mut thread) => {
+                            self.request_previews(&thread.tweets).await?;
+                            thread.direct = match &self.state {
+                                State::Conversations(_) => true,
+                                State::Thread(parent) => parent.direct,
+                                _ => false,
+                            };
+                            self.push_state(State::Thread(thread));
+                        }
+                        Err(e) => {
+                            self.status =
+                                Some(Status::Error(format!("Couldn't fetch the thread: {}", e)))
+                        }
                     },
-                    Message::Tick if self.should_render => {
-                        if self.should_render {
+                    Message::GetOwnAccountResponse(res) => match res {
+                        Ok(data) => self.me = Some(data),
+                        Err(e) => {
+                            self.status =
+                                Some(Status::Error(format!("Couldn't fetch your account: {}", e)))
+                        }
+                    },
+                    Message::DeleteTweetResponse(res, redraft) => match res {
+                        Ok(tweet) => {
+                            self.timelines.remove(&tweet.id);
+                            self.clamp_selection();
+                            self.status = Some(Status::Info("Post deleted".to_string()));
+                            if redraft {
+                                self.push_state(State::Compose(Compose::redraft(&tweet)));
+                            }
+                        }
+                        Err(e) => {
+                            self.status = Some(Status::Error(format!("Couldn't delete: {}", e)))
+                        }
+                    },
+                    Message::DownloadProgress(progress) => {
+                        self.status = Some(Status::Info(progress.describe()));
+                    }
+                    Message::DownloadMediaResponse(res) => {
+                        self.status = Some(match res {
+                            Ok(files) => Status::Info(format!(
+                                "Downloaded {} files to {}",
+                                files,
+                                self.config.download.directory.display()
+                            )),
+                            Err(e) => Status::Error(format!("Download failed: {}", e)),
+                        });
+                    }
+                    Message::Tick => {
+                        self.save_draft();
+                        if self.should_render || self.rendered_at.elapsed() >= CLOCK_REFRESH {
                             self.terminal.draw(|frame| todo!())?;
+                            self.rendered_at = Instant::now();
                         }
                     }
                     Message::Input(e) => {
@@ -96,6 +864,270 @@ impl App {
         Ok(())
     }
 
+    /// Thumbnails of the images, and custom emoji when they can be drawn
+    /// inline
+    async fn request_previews(&mut self, tweets: &[Tweet]) -> Result<()> {
+        let config = &self.config.content_warnings;
+        let media = tweets
+            .iter()
+            .map(|t| t.reblog.as_deref().unwrap_or(t))
+            .filter(|t| !self.reveals.media_hidden(config, t))
+            .flat_map(|t| t.media_attachments.iter())
+            .filter(|m| matches!(m, MediaAttatchment::Image { .. }))
+            .map(|m| m.preview_url());
+        let emojis = tweets
+            .iter()
+            .flat_map(|t| [Some(t), t.reblog.as_deref()])
+            .flatten()
+            .flat_map(|t| t.emojis.iter().chain(&t.account.emojis))
+            .filter(|_| self.graphics != GraphicsProtocol::HalfBlock)
+            .map(|e| e.static_url.as_str());
+        let mut urls: Vec<String> = Vec::new();
+        for url in media.chain(emojis) {
+            if !self.previews.touch(url) && !urls.iter().any(|u| u == url) {
+                urls.push(url.to_string());
+            }
+        }
+        if urls.is_empty() {
+            return Ok(());
+        }
+
+        urls.iter().for_each(|url| {
+            self.previews.insert(url.clone(), Preview::Loading);
+        });
+        self.backend_chan
+            .as_ref()
+            .unwrap()
+            .send(Message::GetPreviews(urls))
+            .await?;
+        Ok(())
+    }
+
+    /// The open conversation list, even under a thread opened from it
+    fn conversations(&mut self) -> Option<&mut ConversationList> {
+        [&mut self.state]
+            .into_iter()
+            .chain(self.history.iter_mut().rev())
+            .find_map(|state| match state {
+                State::Conversations(list) => Some(list),
+                _ => None,
+            })
+    }
+
+    /// The open chat list, even under a chat opened from it
+    fn chats(&mut self) -> Option<&mut ChatList> {
+        [&mut self.state]
+            .into_iter()
+            .chain(self.history.iter_mut().rev())
+            .find_map(|state| match state {
+                State::Chats(list) => Some(list),
+                _ => None,
+            })
+    }
+
+    fn chat_window(&mut self, chat_id: &str) -> Option<&mut ChatWindow> {
+        [&mut self.state]
+            .into_iter()
+            .chain(self.history.iter_mut().rev())
+            .find_map(|state| match state {
+                State::Chat(window) if window.chat.id == chat_id => Some(&mut **window),
+                _ => None,
+            })
+    }
+
+    /// Marks the messages of the open chat as read, when the server
+    /// counts some unread
+    async fn read_chat(&mut self, chat_id: &str) -> Result<()> {
+        let message = match &self.state {
+            State::Chat(window) if window.chat.id == chat_id => match window.newest_id() {
+                Some(id) if window.chat.unread > 0 => {
+                    Message::ReadChat(chat_id.to_string(), id.to_string())
+                }
+                _ => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
+        self.backend_chan.as_ref().unwrap().send(message).await?;
+        Ok(())
+    }
+
+    fn list_manager(&mut self) -> Option<&mut ListManager> {
+        [&mut self.state]
+            .into_iter()
+            .chain(self.history.iter_mut().rev())
+            .find_map(|state| match state {
+                State::Lists(manager) => Some(manager),
+                _ => None,
+            })
+    }
+
+    /// Shows the new title on the tabs of a renamed list
+    fn retitle_list(&mut self, list: &List) {
+        let states = [&mut self.state].into_iter().chain(self.history.iter_mut());
+        let timelines = states
+            .filter_map(|state| match state {
+                State::Timeline(t, _) => Some(t),
+                _ => None,
+            })
+            .chain(self.tabs.iter_mut());
+        for timeline in timelines {
+            if let Timeline::List(l) = timeline
+                && l.id == list.id
+            {
+                *l = list.clone();
+            }
+        }
+    }
+
+    /// Drops the tab of a deleted list, going home from it
+    fn close_list(&mut self, id: &str) {
+        self.close_tabs(|t| matches!(t, Timeline::List(l) if l.id == id));
+        self.timelines.lists.remove(id);
+        self.timelines
+            .forget(|t| matches!(t, Timeline::List(l) if l.id == id));
+    }
+
+    fn hashtag_list(&mut self) -> Option<&mut HashtagList> {
+        [&mut self.state]
+            .into_iter()
+            .chain(self.history.iter_mut().rev())
+            .find_map(|state| match state {
+                State::Hashtags(list) => Some(list),
+                _ => None,
+            })
+    }
+
+    /// Keeps a tab open on a followed hashtag, like the built-in ones
+    fn pin_tag(&mut self, name: &str) {
+        let query = TagQuery::new(name);
+        if !self.followed_tags.contains(&query.tag) {
+            self.followed_tags.push(query.tag.clone());
+        }
+        let timeline = Timeline::Tag(query);
+        if !self.tabs.iter().any(|t| t.is(&timeline)) {
+            self.tabs.push(timeline);
+        }
+    }
+
+    fn unpin_tag(&mut self, name: &str) {
+        let query = TagQuery::new(name);
+        self.followed_tags.retain(|t| *t != query.tag);
+        self.close_tabs(|t| matches!(t, Timeline::Tag(q) if *q == query));
+        self.timelines.tags.remove(&query);
+        self.timelines
+            .forget(|t| matches!(t, Timeline::Tag(q) if *q == query));
+    }
+
+    /// The tab of a followed hashtag, which stays while we follow it
+    fn is_pinned(&self, query: &TagQuery) -> bool {
+        self.followed_tags.contains(&query.tag) && *query == TagQuery::new(&query.tag)
+    }
+
+    /// Drops the tabs, going home from them
+    fn close_tabs(&mut self, closed: impl Fn(&Timeline) -> bool) {
+        self.tabs.retain(|t| !closed(t));
+        for state in [&mut self.state].into_iter().chain(self.history.iter_mut()) {
+            if let State::Timeline(t, _) = state
+                && closed(t)
+            {
+                *state = State::Timeline(Timeline::Home, 0);
+            }
+        }
+    }
+
+    /// The post under the cursor
+    fn selected_tweet(&self) -> Option<&Tweet> {
+        match &self.state {
+            State::Timeline(t, i) => self.timelines.get(t).get(*i),
+            State::Thread(thread) => thread.selected(),
+            _ => None,
+        }
+    }
+
+    /// Shows another timeline, fetching it the first time and opening a
+    /// tab for it
+    async fn switch_timeline(&mut self, timeline: Timeline) -> Result<()> {
+        let message = match timeline {
+            _ if !self.timelines.request(&timeline) => None,
+            Timeline::Home => Some(Message::GetHomeTimeline(None)),
+            Timeline::Local => Some(Message::GetLocalTimeline(None)),
+            Timeline::Public => Some(Message::GetPublicTimeline(None)),
+            Timeline::Bookmarks => Some(Message::GetBookmarks(None)),
+            Timeline::Favourites => Some(Message::GetFavourites(None)),
+            Timeline::List(ref list) => Some(Message::GetListTimeline(list.id.clone(), None)),
+            Timeline::Tag(ref query) => Some(Message::GetTagTimeline(query.clone(), None)),
+        };
+        if !self.tabs.iter().any(|t| t.is(&timeline)) {
+            self.tabs.push(timeline.clone());
+        }
+        self.state = State::Timeline(timeline, 0);
+        if let Some(message) = message {
+            self.backend_chan.as_ref().unwrap().send(message).await?;
+        }
+        Ok(())
+    }
+
+    /// Swaps in the new version of a post in the timelines and the open
+    /// threads, which hold copies of their posts
+    fn replace_tweet(&mut self, tweet: &Tweet) {
+        self.timelines.replace(tweet);
+        for state in [&mut self.state].into_iter().chain(self.history.iter_mut()) {
+            if let State::Thread(thread) = state {
+                replace(&mut thread.tweets, tweet);
+            }
+        }
+    }
+
+    /// Keeps the cursor on the timeline after posts got removed
+    fn clamp_selection(&mut self) {
+        if let State::Timeline(t, i) = &mut self.state {
+            let len = self.timelines.get(t).len();
+            *i = (*i).min(len.saturating_sub(1));
+        }
+    }
+
+    /// Whether the post was written by the logged in account
+    fn is_own(&self, tweet: &Tweet) -> bool {
+        self.me.as_ref().is_some_and(|me| me.id == tweet.account.id)
+    }
+
+    /// Keeps the post being written in the drafts, if it changed.
+    /// Edits of published posts aren't drafts.
+    fn save_draft(&mut self) {
+        let compose = match &mut self.state {
+            State::Compose(compose) if compose.dirty && compose.editing.is_none() => compose,
+            _ => return,
+        };
+        compose.dirty = false;
+        if compose.is_empty() {
+            match compose.draft_id.take() {
+                Some(id) => self.drafts.remove(id),
+                None => return,
+            }
+        } else {
+            let draft = compose.to_draft();
+            compose.draft_id = Some(draft.id);
+            self.drafts.upsert(draft);
+        }
+        if let Err(e) = self.drafts.save() {
+            self.status = Some(Status::Error(format!("Couldn't save the draft: {}", e)));
+        }
+    }
+
+    /// Opens a new screen, remembering the current one
+    fn push_state(&mut self, state: State) {
+        let previous = std::mem::replace(&mut self.state, state);
+        self.history.push(previous);
+    }
+
+    /// Goes back to the previous screen
+    fn pop_state(&mut self) {
+        self.state = self
+            .history
+            .pop()
+            .unwrap_or(State::Timeline(Timeline::Home, 0));
+    }
+
     pub async fn register_backend(&mut self, backend: Sender<Message>) {
         self.backend_chan = Some(backend);
     }
@@ -0,0 +1,218 @@
+use std::{
+    process::{Command, Stdio},
+    thread,
+};
+
+use anyhow::{Result, anyhow};
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Style},
+    widgets::Widget,
+};
+use tokio::task::block_in_place;
+
+use crate::{
+    config::{OpenConfig, Opener},
+    pleroma::tweet::MediaAttatchment,
+    renderer::terminal::Terminal,
+};
+
+use super::input::{pause_input, resume_input};
+
+/// Decides which program is used to open a target
+#[derive(Clone, Copy, Debug)]
+pub enum OpenKind {
+    Link,
+    Image,
+    Video,
+    Audio,
+}
+
+/// Something that can be opened in an external program
+#[derive(Clone, Debug)]
+pub struct OpenTarget {
+    pub kind: OpenKind,
+    pub url: String,
+}
+
+impl OpenTarget {
+    pub fn link(url: &str) -> Self {
+        OpenTarget {
+            kind: OpenKind::Link,
+            url: url.to_string(),
+        }
+    }
+}
+
+impl From<&MediaAttatchment> for OpenTarget {
+    fn from(value: &MediaAttatchment) -> Self {
+        let kind = match value {
+            MediaAttatchment::Image { .. } => OpenKind::Image,
+            MediaAttatchment::Video { .. } | MediaAttatchment::Gifv { .. } => OpenKind::Video,
+            MediaAttatchment::Audio { .. } => OpenKind::Audio,
+            MediaAttatchment::Unknown { .. } => OpenKind::Link,
+        };
+        OpenTarget {
+            kind,
+            url: value.url().to_string(),
+        }
+    }
+}
+
+impl OpenConfig {
+    fn opener(&self, kind: OpenKind) -> &Opener {
+        match kind {
+            OpenKind::Link => &self.link,
+            OpenKind::Image => &self.image,
+            OpenKind::Video => &self.video,
+            OpenKind::Audio => &self.audio,
+        }
+    }
+}
+
+/// Splits a command line into words like a shell would, keeping quoted
+/// and backslash escaped spaces, so paths with spaces work
+fn split_command(command: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(anyhow!("Unclosed quote in {}", command)),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(anyhow!("Unclosed quote in {}", command)),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(anyhow!("Unclosed quote in {}", command)),
+                    }
+                }
+            }
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    word.get_or_insert_default().push(c);
+                }
+            }
+            c if c.is_whitespace() => words.extend(word.take()),
+            c => word.get_or_insert_default().push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Runs the configured program. Terminal programs get the terminal
+/// until they exit, the others are left running in the background.
+pub fn open(terminal: &mut Terminal, config: &OpenConfig, target: &OpenTarget) -> Result<()> {
+    let opener = config.opener(target.kind);
+    let words = split_command(&opener.command)?;
+    let (program, args) = words
+        .split_first()
+        .ok_or(anyhow!("Empty command for {:?}", target.kind))?;
+    let mut command = Command::new(program);
+    command.args(args).arg(&target.url);
+
+    if opener.terminal {
+        // Whatever fails, the terminal and the input are given back
+        pause_input();
+        let status = terminal
+            .suspend()
+            .and_then(|()| Ok(block_in_place(|| command.status())?));
+        let resumed = terminal.resume();
+        resume_input();
+        let status = status?;
+        resumed?;
+        if !status.success() {
+            return Err(anyhow!("{} exited with {}", program, status));
+        }
+    } else {
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        // Reap it once it exits
+        thread::spawn(move || child.wait());
+    }
+    Ok(())
+}
+
+/// Numbered list of targets to choose from
+pub struct OpenPickerWidget<'a> {
+    targets: &'a [OpenTarget],
+    i: usize,
+}
+
+impl<'a> OpenPickerWidget<'a> {
+    pub fn new(targets: &'a [OpenTarget], i: usize) -> Self {
+        OpenPickerWidget { targets, i }
+    }
+}
+
+impl Widget for OpenPickerWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let skip = (self.i + 1).saturating_sub(area.height as usize);
+        self.targets
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(area.height as usize)
+            .enumerate()
+            .for_each(|(row, (i, target))| {
+                let style = if i == self.i {
+                    Style::default().fg(Color::Black).bg(Color::White)
+                } else {
+                    Style::default()
+                };
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + row as u16,
+                    format!("{}. {}", i + 1, target.url),
+                    area.width as usize,
+                    style,
+                );
+            });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::split_command;
+
+    #[test]
+    fn split_quoted_command() {
+        assert_eq!(
+            split_command(r#"'/opt/My Browser/browser' --new-tab "a \"b\" c" d\ e  f"#).unwrap(),
+            [
+                "/opt/My Browser/browser",
+                "--new-tab",
+                r#"a "b" c"#,
+                "d e",
+                "f"
+            ]
+        );
+        assert_eq!(split_command("mpv ''").unwrap(), ["mpv", ""]);
+        assert!(split_command("mpv 'oops").is_err());
+    }
+}
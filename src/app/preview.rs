@@ -0,0 +1,231 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{File, create_dir_all, read, read_dir, remove_file, write},
+    hash::{DefaultHasher, Hash, Hasher},
+    path::PathBuf,
+    time::SystemTime,
+};
+
+use anyhow::{Result, anyhow};
+use image::RgbaImage;
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::Color,
+    widgets::Widget,
+};
+
+use crate::renderer::image::{EncodedImage, GraphicsProtocol, encode};
+
+/// Rows taken by the thumbnails inside a post
+pub const PREVIEW_ROWS: u16 = 6;
+/// Thumbnails are downscaled to fit in a square this big once decoded
+pub const PREVIEW_MAX_SIZE: u32 = 256;
+
+/// State of the thumbnail of a `preview_url`
+pub enum Preview {
+    Loading,
+    Ready(RgbaImage),
+    Failed,
+}
+
+/// Decoded thumbnails by url. Past `capacity` the least recently used
+/// ones are dropped, they get fetched again from the disk cache.
+pub struct Previews {
+    images: HashMap<String, Preview>,
+    /// Least recently used first
+    used: VecDeque<String>,
+    capacity: usize,
+}
+
+impl Previews {
+    pub fn new(capacity: usize) -> Self {
+        Previews {
+            images: HashMap::new(),
+            used: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn images(&self) -> &HashMap<String, Preview> {
+        &self.images
+    }
+
+    /// Marks the url as just used, false if it isn't known yet
+    pub fn touch(&mut self, url: &str) -> bool {
+        match self.used.iter().position(|u| u == url) {
+            Some(i) => {
+                let url = self.used.remove(i).unwrap();
+                self.used.push_back(url);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn insert(&mut self, url: String, preview: Preview) {
+        if !self.touch(&url) {
+            self.used.push_back(url.clone());
+        }
+        self.images.insert(url, preview);
+        self.evict();
+    }
+
+    /// Ones still loading are kept, their response would add them back
+    fn evict(&mut self) {
+        while self.images.len() > self.capacity {
+            let oldest = self
+                .used
+                .iter()
+                .position(|u| !matches!(self.images.get(u), Some(Preview::Loading)));
+            match oldest.and_then(|i| self.used.remove(i)) {
+                Some(url) => {
+                    self.images.remove(&url);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+fn url_hash(url: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Downloaded previews on disk. Once `max_size` bytes are exceeded the
+/// least recently used files are removed.
+pub struct PreviewCache {
+    dir: PathBuf,
+    max_size: u64,
+}
+
+impl PreviewCache {
+    pub fn new(max_size: u64) -> Result<Self> {
+        let dir = dirs::cache_dir()
+            .ok_or(anyhow!("No cache directory available"))?
+            .join("plerustax")
+            .join("previews");
+        create_dir_all(&dir)?;
+        Ok(PreviewCache { dir, max_size })
+    }
+
+    fn path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}", url_hash(url)))
+    }
+
+    pub fn get(&self, url: &str) -> Option<Vec<u8>> {
+        let path = self.path(url);
+        let data = read(&path).ok()?;
+        // Bump it so eviction sees it as recently used
+        if let Ok(f) = File::options().append(true).open(&path) {
+            let _ = f.set_modified(SystemTime::now());
+        }
+        Some(data)
+    }
+
+    pub fn insert(&self, url: &str, data: &[u8]) -> Result<()> {
+        write(self.path(url), data)?;
+        self.evict()
+    }
+
+    fn evict(&self) -> Result<()> {
+        let mut files = read_dir(&self.dir)?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let metadata = e.metadata().ok()?;
+                Some((e.path(), metadata.len(), metadata.modified().ok()?))
+            })
+            .collect::<Vec<_>>();
+        files.sort_by_key(|(_, _, modified)| *modified);
+
+        let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+        for (path, len, _) in files {
+            if total <= self.max_size {
+                break;
+            }
+            remove_file(path)?;
+            total -= len;
+        }
+        Ok(())
+    }
+}
+
+/// Draws an image over the whole area
+pub struct PreviewWidget<'a> {
+    image: &'a RgbaImage,
+    protocol: GraphicsProtocol,
+    url: &'a str,
+}
+
+impl<'a> PreviewWidget<'a> {
+    pub fn new(image: &'a RgbaImage, protocol: GraphicsProtocol, url: &'a str) -> Self {
+        PreviewWidget {
+            image,
+            protocol,
+            url,
+        }
+    }
+}
+
+impl Widget for PreviewWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        if area.is_empty() {
+            return;
+        }
+        // Kitty ids can't be 0
+        let id = (url_hash(self.url) as u32).max(1);
+        match encode(self.image, self.protocol, area.width, area.height, id) {
+            EncodedImage::Escape(escape) => {
+                // The terminal draws the image itself, the cells under it
+                // are reserved so that nothing gets printed over it.
+                for y in area.top()..area.bottom() {
+                    for x in area.left()..area.right() {
+                        buf[(x, y)].set_skip(true);
+                    }
+                }
+                buf[(area.left(), area.top())]
+                    .set_skip(false)
+                    .set_symbol(&escape);
+            }
+            EncodedImage::HalfBlock(pixels) => {
+                for y in 0..area.height {
+                    for x in 0..area.width {
+                        let top = pixels.get_pixel(x as u32, y as u32 * 2);
+                        let bottom = pixels.get_pixel(x as u32, y as u32 * 2 + 1);
+                        buf[(area.left() + x, area.top() + y)]
+                            .set_char('\u{2580}')
+                            .set_fg(Color::Rgb(top[0], top[1], top[2]))
+                            .set_bg(Color::Rgb(bottom[0], bottom[1], bottom[2]));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use image::RgbaImage;
+
+    use super::{Preview, Previews};
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut previews = Previews::new(2);
+        previews.insert("a".to_string(), Preview::Ready(RgbaImage::new(1, 1)));
+        previews.insert("b".to_string(), Preview::Loading);
+        previews.insert("c".to_string(), Preview::Failed);
+        // "a" was the oldest that wasn't loading
+        assert!(!previews.images().contains_key("a"));
+
+        assert!(previews.touch("b"));
+        previews.insert("d".to_string(), Preview::Failed);
+        assert!(!previews.images().contains_key("c"));
+        assert!(previews.images().contains_key("b"));
+        assert!(previews.images().contains_key("d"));
+    }
+}
@@ -0,0 +1,178 @@
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::pleroma::{
+    emoji::{CustomEmoji, EmojiReaction},
+    tweet::Tweet,
+};
+
+use super::emoji::is_shortcode;
+
+/// Offered before any custom emoji
+const COMMON_EMOJI: [&str; 8] = [
+    "\u{1f44d}",
+    "\u{2764}\u{fe0f}",
+    "\u{1f606}",
+    "\u{1f62e}",
+    "\u{1f622}",
+    "\u{1f621}",
+    "\u{1f389}",
+    "\u{1f914}",
+];
+
+/// Choosing an emoji to react with, or to take our reaction back
+#[derive(Clone)]
+pub struct ReactionPicker {
+    pub tweet_id: String,
+    /// Emoji we already reacted with
+    pub mine: Vec<String>,
+    /// Common emoji, the ones on the post, then the custom ones
+    options: Vec<String>,
+    /// Filter on the shortcodes, or an emoji to react with directly
+    pub query: String,
+    pub i: usize,
+}
+
+impl ReactionPicker {
+    pub fn new(tweet: &Tweet, custom_emojis: &[CustomEmoji]) -> Self {
+        let reactions = &tweet.pleroma.emoji_reactions;
+        let mut options: Vec<String> = Vec::new();
+        let common = COMMON_EMOJI.iter().map(|e| e.to_string());
+        let on_post = reactions.iter().map(|r| r.name.clone());
+        let custom = custom_emojis
+            .iter()
+            .filter(|e| e.visible_in_picker)
+            .map(|e| e.shortcode.clone());
+        for option in common.chain(on_post).chain(custom) {
+            if !options.contains(&option) {
+                options.push(option);
+            }
+        }
+        ReactionPicker {
+            tweet_id: tweet.id.clone(),
+            mine: reactions
+                .iter()
+                .filter(|r| r.me)
+                .map(|r| r.name.clone())
+                .collect(),
+            options,
+            query: String::new(),
+            i: 0,
+        }
+    }
+
+    pub fn matches(&self) -> Vec<&str> {
+        let query = self.query.trim_matches(':');
+        self.options
+            .iter()
+            .map(|o| o.as_str())
+            .filter(|o| o.contains(query))
+            .collect()
+    }
+
+    /// The highlighted emoji, or what was typed when nothing matches it
+    pub fn selected(&self) -> Option<String> {
+        match self.matches().get(self.i) {
+            Some(emoji) => Some(emoji.to_string()),
+            None if !self.query.is_empty() => Some(self.query.trim_matches(':').to_string()),
+            None => None,
+        }
+    }
+
+    pub fn down(&mut self) {
+        if self.i + 1 < self.matches().len() {
+            self.i += 1;
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.i = self.i.saturating_sub(1);
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.i = 0;
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.i = 0;
+    }
+}
+
+fn display(name: &str) -> String {
+    if is_shortcode(name) {
+        format!(":{}:", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// `emoji count` for each reaction, ours highlighted
+pub fn reactions_line(reactions: &[EmojiReaction]) -> Line<'static> {
+    Line::default().spans(reactions.iter().flat_map(|r| {
+        [
+            Span::default()
+                .content(format!("{} {}", display(&r.name), r.count))
+                .style(if r.me {
+                    Style::default().fg(Color::Cyan)
+                } else {
+                    Style::default()
+                }),
+            Span::default().content("  "),
+        ]
+    }))
+}
+
+pub struct ReactionPickerWidget<'a> {
+    picker: &'a ReactionPicker,
+}
+
+impl<'a> From<&'a ReactionPicker> for ReactionPickerWidget<'a> {
+    fn from(value: &'a ReactionPicker) -> Self {
+        ReactionPickerWidget { picker: value }
+    }
+}
+
+impl Widget for ReactionPickerWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        buf.set_stringn(
+            area.left(),
+            area.top(),
+            format!("React: {}", self.picker.query),
+            area.width as usize,
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+
+        let rows = area.height.saturating_sub(1) as usize;
+        let skip = (self.picker.i + 1).saturating_sub(rows);
+        self.picker
+            .matches()
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(rows)
+            .for_each(|(i, emoji)| {
+                let mine = self.picker.mine.iter().any(|m| m == emoji);
+                let style = if i == self.picker.i {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1 + (i - skip) as u16,
+                    format!("{} {}", if mine { "\u{2713}" } else { " " }, display(emoji)),
+                    area.width as usize,
+                    style,
+                );
+            });
+    }
+}
@@ -0,0 +1,138 @@
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+
+use crate::pleroma::scheduled::ScheduledTweet;
+
+/// The posts waiting to be published
+#[derive(Clone, Default)]
+pub struct ScheduledList {
+    /// `None` until the server answers
+    pub tweets: Option<Vec<ScheduledTweet>>,
+    pub i: usize,
+    /// New time being typed for the selected post
+    pub reschedule: Option<String>,
+}
+
+impl ScheduledList {
+    pub fn selected(&self) -> Option<&ScheduledTweet> {
+        self.tweets.as_ref()?.get(self.i)
+    }
+
+    pub fn down(&mut self) {
+        let len = self.tweets.as_ref().map(|t| t.len()).unwrap_or(0);
+        if self.i + 1 < len {
+            self.i += 1;
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.i = self.i.saturating_sub(1);
+    }
+
+    /// Puts the updated post back in the list, keeping it sorted by time
+    pub fn update(&mut self, tweet: ScheduledTweet) {
+        if let Some(tweets) = &mut self.tweets {
+            tweets.retain(|t| t.id != tweet.id);
+            tweets.push(tweet);
+            tweets.sort_by(|a, b| a.scheduled_at.cmp(&b.scheduled_at));
+        }
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        if let Some(tweets) = &mut self.tweets {
+            tweets.retain(|t| t.id != id);
+            self.i = self.i.min(tweets.len().saturating_sub(1));
+        }
+    }
+}
+
+pub struct ScheduledListWidget<'a> {
+    list: &'a ScheduledList,
+}
+
+impl<'a> From<&'a ScheduledList> for ScheduledListWidget<'a> {
+    fn from(value: &'a ScheduledList) -> Self {
+        ScheduledListWidget { list: value }
+    }
+}
+
+impl Widget for ScheduledListWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let header = match &self.list.reschedule {
+            Some(at) => format!("New time (YYYY-MM-DD HH:MM, Enter to save): {}", at),
+            None => "Scheduled posts | Reschedule (r) | Cancel (x) | Back (Esc)".to_string(),
+        };
+        buf.set_stringn(
+            area.left(),
+            area.top(),
+            header,
+            area.width as usize,
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+
+        let tweets = match &self.list.tweets {
+            Some(tweets) if tweets.is_empty() => {
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1,
+                    "Nothing scheduled",
+                    area.width as usize,
+                    Style::default().fg(Color::DarkGray),
+                );
+                return;
+            }
+            Some(tweets) => tweets,
+            None => {
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1,
+                    "Loading...",
+                    area.width as usize,
+                    Style::default().fg(Color::DarkGray),
+                );
+                return;
+            }
+        };
+
+        let rows = area.height.saturating_sub(1) as usize;
+        let skip = (self.list.i + 1).saturating_sub(rows);
+        tweets
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(rows)
+            .for_each(|(i, tweet)| {
+                let attachments = match tweet.media_attachments.len() {
+                    0 => String::new(),
+                    n => format!(" \u{1f4ce}{}", n),
+                };
+                let cw = match tweet.params.spoiler_text.as_deref() {
+                    Some(cw) if !cw.is_empty() => format!("\u{26a0} {} ", cw),
+                    _ => String::new(),
+                };
+                buf.set_stringn(
+                    area.left(),
+                    area.top() + 1 + (i - skip) as u16,
+                    format!(
+                        "{} {}{}{}",
+                        tweet.scheduled_at,
+                        cw,
+                        tweet.params.text.replace('\n', " "),
+                        attachments
+                    ),
+                    area.width as usize,
+                    if i == self.list.i {
+                        Style::default().add_modifier(Modifier::REVERSED)
+                    } else {
+                        Style::default()
+                    },
+                );
+            });
+    }
+}
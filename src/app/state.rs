@@ -1,6 +1,41 @@
+use crate::pleroma::{account::Account, list::List, tag::TagQuery, tweet::TweetRevision};
+
+use super::{
+    accounts::AccountList,
+    chats::{ChatList, ChatWindow},
+    compose::Compose,
+    confirm::ConfirmAction,
+    conversations::ConversationList,
+    hashtags::HashtagList,
+    lists::ListManager,
+    open::OpenTarget,
+    reactions::ReactionPicker,
+    scheduled::ScheduledList,
+    thread::Thread,
+};
+
 #[derive(Clone)]
 pub enum State {
     Timeline(Timeline, usize),
+    Compose(Compose),
+    /// Choosing which link or attachment to open
+    OpenPicker(Vec<OpenTarget>, usize),
+    /// Waiting for a yes or no, drawn over the previous screen
+    Confirm(ConfirmAction),
+    /// Past versions of an edited post and the one being compared
+    History(Vec<TweetRevision>, usize),
+    Scheduled(ScheduledList),
+    /// The saved drafts and the selected one
+    Drafts(usize),
+    Accounts(AccountList),
+    Profile(Box<Account>),
+    ReactionPicker(ReactionPicker),
+    Thread(Thread),
+    Conversations(ConversationList),
+    Chats(ChatList),
+    Chat(Box<ChatWindow>),
+    Lists(ListManager),
+    Hashtags(HashtagList),
 }
 
 #[derive(Clone)]
@@ -8,4 +43,40 @@ pub enum Timeline {
     Home,
     Local,
     Public,
+    Bookmarks,
+    Favourites,
+    List(List),
+    Tag(TagQuery),
+}
+
+impl Timeline {
+    /// The timelines always there, before the opened ones
+    pub const BUILT_IN: [Timeline; 5] = [
+        Timeline::Home,
+        Timeline::Local,
+        Timeline::Public,
+        Timeline::Bookmarks,
+        Timeline::Favourites,
+    ];
+
+    pub fn title(&self) -> String {
+        match self {
+            Timeline::Home => "Home".to_string(),
+            Timeline::Local => "Local".to_string(),
+            Timeline::Public => "Public".to_string(),
+            Timeline::Bookmarks => "Bookmarks".to_string(),
+            Timeline::Favourites => "Favourites".to_string(),
+            Timeline::List(list) => list.title.clone(),
+            Timeline::Tag(query) => query.to_string(),
+        }
+    }
+
+    /// Same timeline, even if a list got renamed since
+    pub fn is(&self, other: &Timeline) -> bool {
+        match (self, other) {
+            (Timeline::List(a), Timeline::List(b)) => a.id == b.id,
+            (Timeline::Tag(a), Timeline::Tag(b)) => a == b,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
 }
@@ -0,0 +1,41 @@
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Style},
+    widgets::Widget,
+};
+
+/// Message shown at the bottom of the screen
+pub enum Status {
+    Info(String),
+    Error(String),
+}
+
+pub struct StatusBarWidget<'a> {
+    status: &'a Status,
+}
+
+impl<'a> From<&'a Status> for StatusBarWidget<'a> {
+    fn from(value: &'a Status) -> Self {
+        StatusBarWidget { status: value }
+    }
+}
+
+impl Widget for StatusBarWidget<'_> {
+    /// Expecting to be 1 line
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let (text, style) = match self.status {
+            Status::Info(text) => (text, Style::default()),
+            Status::Error(text) => (text, Style::default().fg(Color::Red)),
+        };
+        buf.set_stringn(
+            area.left(),
+            area.top(),
+            text.replace('\n', " "),
+            area.width as usize,
+            style,
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use crate::pleroma::tweet::{Context, Tweet};
+
+/// A post with the ones it replies to and the replies to it
+#[derive(Clone)]
+pub struct Thread {
+    pub tweets: Vec<Tweet>,
+    /// The post the thread was opened from
+    pub focus: usize,
+    pub i: usize,
+    /// Opened from the direct messages, replies stay direct
+    pub direct: bool,
+}
+
+impl Thread {
+    pub fn new(tweet: Tweet, context: Context) -> Self {
+        let focus = context.ancestors.len();
+        Thread {
+            tweets: context
+                .ancestors
+                .into_iter()
+                .chain([tweet])
+                .chain(context.descendants)
+                .collect(),
+            focus,
+            i: focus,
+            direct: false,
+        }
+    }
+
+    pub fn selected(&self) -> Option<&Tweet> {
+        self.tweets.get(self.i)
+    }
+
+    pub fn down(&mut self) {
+        if self.i + 1 < self.tweets.len() {
+            self.i += 1;
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.i = self.i.saturating_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pleroma::tweet::{Context, Tweet, test::fixture};
+
+    use super::Thread;
+
+    fn tweet(id: &str) -> Tweet {
+        let mut tweet: Tweet = fixture("mastodon_status.json");
+        tweet.id = id.to_string();
+        tweet
+    }
+
+    #[test]
+    fn focus_on_the_opened_post() {
+        let context = Context {
+            ancestors: vec![tweet("1"), tweet("2")],
+            descendants: vec![tweet("4")],
+        };
+        let mut thread = Thread::new(tweet("3"), context);
+        let ids: Vec<&str> = thread.tweets.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["1", "2", "3", "4"]);
+        assert_eq!(thread.focus, 2);
+        assert_eq!(thread.selected().unwrap().id, "3");
+
+        thread.down();
+        thread.down();
+        assert_eq!(thread.selected().unwrap().id, "4");
+        (0..5).for_each(|_| thread.up());
+        assert_eq!(thread.selected().unwrap().id, "1");
+        assert_eq!(thread.focus, 2);
+
+        let alone = Thread::new(
+            tweet("5"),
+            Context {
+                ancestors: Vec::new(),
+                descendants: Vec::new(),
+            },
+        );
+        assert_eq!(alone.focus, 0);
+        assert_eq!(alone.selected().unwrap().id, "5");
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Local, NaiveDateTime, SecondsFormat, TimeDelta, TimeZone, Utc};
+
+/// How far ahead the servers want scheduled posts
+const MIN_SCHEDULE: TimeDelta = TimeDelta::minutes(5);
+/// Local times accepted when scheduling, besides RFC 3339
+const SCHEDULE_FORMATS: [&str; 3] = ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S"];
+
+/// Largest unit only, like `5m` or `3h`
+fn short(delta: TimeDelta) -> String {
+    let seconds = delta.num_seconds().max(0);
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3600 => format!("{}m", seconds / 60),
+        3600..86400 => format!("{}h", seconds / 3600),
+        86400..31536000 => format!("{}d", seconds / 86400),
+        _ => format!("{}y", seconds / 31536000),
+    }
+}
+
+/// How long ago `at` was, for post headers
+pub fn age(at: &DateTime<Utc>, now: &DateTime<Utc>) -> String {
+    short(*now - *at)
+}
+
+/// `at` in the local time zone, for the thread view
+pub fn absolute(at: &DateTime<Utc>) -> String {
+    at.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+/// Time left on a poll
+pub fn countdown(expires_at: &DateTime<Utc>, now: &DateTime<Utc>) -> String {
+    if expires_at <= now {
+        "closed".to_string()
+    } else {
+        format!("{} left", short(*expires_at - *now))
+    }
+}
+
+/// When to publish a post, typed as a local time like `2024-06-11 21:30`
+/// or with an offset, as the RFC 3339 the API expects
+pub fn schedule(text: &str, now: &DateTime<Utc>) -> Result<String> {
+    let text = text.trim();
+    let at = match DateTime::parse_from_rfc3339(text) {
+        Ok(at) => at.with_timezone(&Utc),
+        Err(_) => {
+            let naive = SCHEDULE_FORMATS
+                .iter()
+                .find_map(|f| NaiveDateTime::parse_from_str(text, f).ok())
+                .ok_or(anyhow!("Not a time, use YYYY-MM-DD HH:MM: {}", text))?;
+            Local
+                .from_local_datetime(&naive)
+                .single()
+                .ok_or(anyhow!("{} is skipped or repeated locally", text))?
+                .with_timezone(&Utc)
+        }
+    };
+    if at < *now + MIN_SCHEDULE {
+        return Err(anyhow!("Schedule at least 5 minutes ahead"));
+    }
+    Ok(at.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{DateTime, Local, NaiveDateTime, TimeDelta, TimeZone, Utc};
+
+    use super::{age, countdown, schedule};
+
+    #[test]
+    fn relative_times() {
+        let now: DateTime<Utc> = "2024-06-11T21:03:00Z".parse().unwrap();
+        assert_eq!(age(&(now - TimeDelta::seconds(12)), &now), "12s");
+        assert_eq!(age(&(now - TimeDelta::minutes(5)), &now), "5m");
+        assert_eq!(age(&(now - TimeDelta::hours(3)), &now), "3h");
+        assert_eq!(age(&(now - TimeDelta::days(2)), &now), "2d");
+        assert_eq!(age(&(now - TimeDelta::days(800)), &now), "2y");
+        // Clock skew with the server
+        assert_eq!(age(&(now + TimeDelta::seconds(3)), &now), "0s");
+        assert_eq!(countdown(&(now + TimeDelta::hours(25)), &now), "1d left");
+        assert_eq!(countdown(&now, &now), "closed");
+    }
+
+    #[test]
+    fn schedule_times() {
+        let now: DateTime<Utc> = "2024-06-11T21:03:00Z".parse().unwrap();
+        assert_eq!(
+            schedule(" 2024-06-12T10:00:00+02:00 ", &now).unwrap(),
+            "2024-06-12T08:00:00Z"
+        );
+        let naive = NaiveDateTime::parse_from_str("2024-07-01 09:30", "%Y-%m-%d %H:%M").unwrap();
+        let local = Local
+            .from_local_datetime(&naive)
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            schedule("2024-07-01 09:30", &now).unwrap(),
+            local.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        );
+        assert!(schedule("tomorrow", &now).is_err());
+        assert!(schedule("2024-06-11T21:05:00Z", &now).is_err());
+        assert!(schedule("2024-06-11T21:08:00Z", &now).is_ok());
+    }
+}
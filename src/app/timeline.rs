@@ -1,56 +1,424 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use image::RgbaImage;
 use ratatui::{
     prelude::{Buffer, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::Widget,
+    widgets::{Block, Widget},
+};
+use unicode_width::UnicodeWidthChar;
+
+use crate::{
+    config::ContentWarningConfig,
+    pleroma::tweet::{MediaAttatchment, Tweet},
+    renderer::image::{GraphicsProtocol, columns_for},
 };
 
-use crate::pleroma::tweet::Tweet;
+use super::{
+    content_warning::Reveals,
+    emoji::{self, EmojiText},
+    preview::{PREVIEW_ROWS, Preview, PreviewWidget},
+    reactions::reactions_line,
+    time::{absolute, age, countdown},
+};
 
 pub struct TimelineTweetWidget<'a> {
     tweet: &'a Tweet,
+    /// Loaded image thumbnails with their `preview_url`
+    previews: Vec<(&'a str, &'a RgbaImage)>,
+    /// Every loaded image, for the custom emoji
+    images: Option<&'a HashMap<String, Preview>>,
+    graphics: GraphicsProtocol,
+    /// Content hidden behind the CW
+    collapsed: bool,
+    hide_media: bool,
+    now: DateTime<Utc>,
+    /// Local dates instead of ages
+    absolute_time: bool,
 }
 
 impl<'a> From<&'a Tweet> for TimelineTweetWidget<'a> {
     fn from(value: &'a Tweet) -> Self {
-        TimelineTweetWidget { tweet: value }
+        TimelineTweetWidget {
+            tweet: value,
+            previews: Vec::new(),
+            images: None,
+            graphics: GraphicsProtocol::HalfBlock,
+            collapsed: !value
+                .reblog
+                .as_deref()
+                .unwrap_or(value)
+                .spoiler_text
+                .is_empty(),
+            hide_media: value.reblog.as_deref().unwrap_or(value).sensitive,
+            now: Utc::now(),
+            absolute_time: false,
+        }
+    }
+}
+
+impl<'a> TimelineTweetWidget<'a> {
+    pub fn previews(
+        mut self,
+        previews: &'a HashMap<String, Preview>,
+        graphics: GraphicsProtocol,
+    ) -> Self {
+        self.previews = self
+            .tweet
+            .media_attachments
+            .iter()
+            .filter(|m| matches!(m, MediaAttatchment::Image { .. }))
+            .filter_map(|m| match previews.get_key_value(m.preview_url()) {
+                Some((url, Preview::Ready(image))) => Some((url.as_str(), image)),
+                _ => None,
+            })
+            .collect();
+        self.images = Some(previews);
+        self.graphics = graphics;
+        self
+    }
+
+    pub fn content_warnings(mut self, config: &ContentWarningConfig, reveals: &Reveals) -> Self {
+        self.collapsed = reveals.collapsed(config, self.tweet);
+        self.hide_media = reveals.media_hidden(config, self.tweet);
+        self
+    }
+
+    pub fn absolute_time(mut self, absolute_time: bool) -> Self {
+        self.absolute_time = absolute_time;
+        self
+    }
+
+    fn time(&self, at: &DateTime<Utc>) -> String {
+        if self.absolute_time {
+            absolute(at)
+        } else {
+            age(at, &self.now)
+        }
+    }
+
+    fn poll_height(&self) -> u16 {
+        self.tweet.poll.is_some() as u16
+    }
+
+    /// Title and description between borders
+    fn card_height(&self) -> u16 {
+        if self.tweet.card.is_some() { 4 } else { 0 }
+    }
+
+    fn attachments_height(&self) -> u16 {
+        match (self.tweet.media_attachments.len() as u16, self.hide_media) {
+            (0, _) => 0,
+            (_, true) => 1,
+            (n, false) if self.previews.is_empty() => n,
+            (n, false) => n + PREVIEW_ROWS,
+        }
+    }
+
+    fn reactions_height(&self) -> u16 {
+        if self.tweet.pleroma.emoji_reactions.is_empty() {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// The post carrying the CW, the boosted one for boosts
+    fn warned(&self) -> &'a Tweet {
+        self.tweet.reblog.as_deref().unwrap_or(self.tweet)
+    }
+
+    /// What's under the content when it isn't collapsed
+    fn body_height(&self) -> u16 {
+        self.poll_height() + self.card_height() + self.attachments_height()
+    }
+
+    /// Header, CW, content, the poll, the link card, one line per
+    /// attachment, the thumbnails, the action bar and the reactions
+    pub fn height(&self) -> u16 {
+        let has_cw = !self.warned().spoiler_text.is_empty();
+        let height = match (has_cw, self.collapsed) {
+            (true, true) => 3,
+            (true, false) => 4 + self.body_height(),
+            (false, _) => 3 + self.body_height(),
+        };
+        height + self.reactions_height()
+    }
+
+    /// Visibility, name, badges, age and edit time. The name is cut to keep
+    /// the rest in `width` cells.
+    fn header(&self, width: u16) -> Line<'a> {
+        let account = &self.tweet.account;
+        let dim = Style::default().fg(Color::DarkGray);
+        let mut name = match account.display_name.as_str() {
+            "" => Vec::new(),
+            display_name => {
+                let mut name = emoji::spans(
+                    display_name,
+                    &account.emojis,
+                    Style::default().add_modifier(Modifier::BOLD),
+                );
+                name.push(Span::default().content(" "));
+                name
+            }
+        };
+        name.push(
+            Span::default()
+                .content(format!("@{}", account.acct))
+                .style(dim),
+        );
+
+        let mut badges = Vec::new();
+        if account.bot {
+            badges.push(
+                Span::default()
+                    .content(" [bot]")
+                    .style(Style::default().fg(Color::Cyan)),
+            );
+        }
+        if account.locked {
+            badges.push(Span::default().content(" \u{1f512}"));
+        }
+        if account.is_admin() {
+            badges.push(
+                Span::default()
+                    .content(" [admin]")
+                    .style(Style::default().fg(Color::Red)),
+            );
+        } else if account.is_moderator() {
+            badges.push(
+                Span::default()
+                    .content(" [mod]")
+                    .style(Style::default().fg(Color::Magenta)),
+            );
+        }
+        if self.tweet.pleroma.local {
+            badges.push(
+                Span::default()
+                    .content(" [local]")
+                    .style(Style::default().fg(Color::Green)),
+            );
+        }
+        badges.push(
+            Span::default()
+                .content(format!(" \u{b7} {}", self.time(&self.tweet.created_at)))
+                .style(dim),
+        );
+
+        if let Some(edited_at) = &self.tweet.edited_at {
+            badges.push(
+                Span::default()
+                    .content(format!(
+                        " (edited {}) [h for history]",
+                        self.time(edited_at)
+                    ))
+                    .style(dim),
+            );
+        }
+
+        let icon = Span::default()
+            .content(format!("{} ", self.tweet.visibility.icon()))
+            .style(dim);
+        let fixed = icon.width() + badges.iter().map(Span::width).sum::<usize>();
+        let mut header = vec![icon];
+        header.extend(truncate(name, (width as usize).saturating_sub(fixed)));
+        header.extend(badges);
+        Line::default().spans(header)
+    }
+
+    /// Draws from the top of `area`, taking `card_height()` lines
+    fn render_card(&self, area: Rect, buf: &mut Buffer) {
+        let card = match &self.tweet.card {
+            Some(card) => card,
+            None => return,
+        };
+        let block = Block::bordered()
+            .title(card.provider_name.as_str())
+            .border_style(Style::default().fg(Color::DarkGray));
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let title = match card.title.as_str() {
+            "" => card.url.as_str(),
+            title => title,
+        };
+        buf.set_stringn(
+            inner.left(),
+            inner.top(),
+            title,
+            inner.width as usize,
+            Style::default().add_modifier(Modifier::BOLD),
+        );
+        buf.set_stringn(
+            inner.left(),
+            inner.top() + 1,
+            card.description.replace('\n', " "),
+            inner.width as usize,
+            Style::default().fg(Color::DarkGray),
+        );
+    }
+
+    /// Draws from the top of `area`, taking `attachments_height()` lines
+    fn render_attachments(&self, area: Rect, buf: &mut Buffer) {
+        if self.tweet.media_attachments.is_empty() {
+            return;
+        }
+        if self.hide_media {
+            buf.set_stringn(
+                area.left(),
+                area.top(),
+                format!(
+                    "\u{26a0} {} sensitive attachments hidden [v to show]",
+                    self.tweet.media_attachments.len()
+                ),
+                area.width as usize,
+                Style::default().fg(Color::DarkGray),
+            );
+            return;
+        }
+
+        self.tweet
+            .media_attachments
+            .iter()
+            .enumerate()
+            .for_each(|(i, media)| {
+                let line = match (media, media.description()) {
+                    (_, Some(description)) => Line::default().spans([
+                        Span::default().content(format!("{} ", media.icon())),
+                        Span::default().content(description.replace('\n', " ")),
+                    ]),
+                    (MediaAttatchment::Image { .. }, None) => Line::default().spans([
+                        Span::default().content(format!("{} ", media.icon())),
+                        Span::default()
+                            .content("no alt text")
+                            .style(Style::default().fg(Color::Red)),
+                    ]),
+                    (_, None) => Line::default().spans([
+                        Span::default().content(format!("{} ", media.icon())),
+                        Span::default()
+                            .content("no description")
+                            .style(Style::default().fg(Color::DarkGray)),
+                    ]),
+                };
+                buf.set_line(area.left(), area.top() + i as u16, &line, area.width);
+            });
+
+        let previews_top = area.top() + self.tweet.media_attachments.len() as u16;
+        let mut x = area.left();
+        for (url, image) in &self.previews {
+            let width = columns_for(image, PREVIEW_ROWS).min(area.right().saturating_sub(x));
+            if width == 0 {
+                break;
+            }
+            PreviewWidget::new(image, self.graphics, url)
+                .render(Rect::new(x, previews_top, width, PREVIEW_ROWS), buf);
+            x += width + 1;
+        }
+    }
+}
+
+/// Cuts `spans` to `width` cells, ending with an ellipsis when cut
+fn truncate(spans: Vec<Span<'_>>, width: usize) -> Vec<Span<'_>> {
+    let total: usize = spans.iter().map(Span::width).sum();
+    if total <= width {
+        return spans;
+    }
+    let mut left = width.saturating_sub(1);
+    let mut res = Vec::new();
+    for span in spans {
+        let mut content = String::new();
+        for c in span.content.chars() {
+            let c_width = c.width().unwrap_or(0);
+            if c_width > left {
+                break;
+            }
+            left -= c_width;
+            content.push(c);
+        }
+        let full = content.len() == span.content.len();
+        res.push(Span::default().content(content).style(span.style));
+        if !full {
+            break;
+        }
     }
+    if width > 0 {
+        res.push(Span::default().content("\u{2026}"));
+    }
+    res
 }
 
 impl Widget for TimelineTweetWidget<'_> {
-    /// Expecting to always be 3 lines
+    /// Expecting to always be `height()` lines
     fn render(self, area: Rect, buf: &mut Buffer)
     where
         Self: Sized,
     {
-        buf.set_string(
+        buf.set_line(
             area.left(),
             area.top(),
-            if self.tweet.account.acct.len() as u16 > area.width {
-                self.tweet
-                    .account
-                    .acct
-                    .chars()
-                    .take(area.width as usize - 3)
-                    .collect::<String>()
-            } else {
-                self.tweet.account.acct.clone()
-            },
-            Style::default(),
+            &self.header(area.width),
+            area.width,
         );
 
-        buf.set_string(
-            area.left(),
-            area.top() + 1,
-            self.tweet
-                .content
-                .chars()
-                .take(area.width as usize)
-                .collect::<String>(),
-            Style::default(),
-        );
+        let mut y = area.top() + 1;
+        let warned = self.warned();
+        if !warned.spoiler_text.is_empty() {
+            let hint = if self.collapsed {
+                format!(
+                    " [c to expand, {} attachments]",
+                    warned.media_attachments.len()
+                )
+            } else {
+                String::new()
+            };
+            let warning = Style::default().fg(Color::Yellow);
+            let mut spans = vec![Span::default().content("\u{26a0} ").style(warning)];
+            spans.extend(emoji::spans(&warned.spoiler_text, &warned.emojis, warning));
+            spans.push(
+                Span::default()
+                    .content(hint)
+                    .style(Style::default().fg(Color::DarkGray)),
+            );
+            buf.set_line(area.left(), y, &Line::default().spans(spans), area.width);
+            y += 1;
+        }
+
+        if !self.collapsed {
+            let content = self.tweet.plain_content().replace('\n', " ");
+            let mut content = EmojiText::new(&content, &self.tweet.emojis);
+            if let Some(images) = self.images {
+                content = content.images(images, self.graphics);
+            }
+            content.render(Rect::new(area.left(), y, area.width, 1), buf);
+            y += 1;
+            if let Some(poll) = &self.tweet.poll {
+                let state = match &poll.expires_at {
+                    _ if poll.expired => "closed".to_string(),
+                    Some(expires_at) => countdown(expires_at, &self.now),
+                    None => "open".to_string(),
+                };
+                buf.set_stringn(
+                    area.left(),
+                    y,
+                    format!("\u{2630} Poll, {} votes, {}", poll.votes_count, state),
+                    area.width as usize,
+                    Style::default().fg(Color::Cyan),
+                );
+                y += 1;
+            }
+            self.render_card(
+                Rect::new(area.left(), y, area.width, self.card_height()),
+                buf,
+            );
+            y += self.card_height();
+            self.render_attachments(
+                Rect::new(area.left(), y, area.width, self.attachments_height()),
+                buf,
+            );
+        }
 
-        let spacing: String = (0..(area.width - 6) / 4).map(|_| ' ').collect();
+        let spacing: String = (0..area.width.saturating_sub(8) / 5).map(|_| ' ').collect();
         let buttons = [
             Span::default().content(&spacing),
             Span::default().content(format!("\u{21b5}{}", self.tweet.replies_count)),
@@ -72,25 +440,78 @@ impl Widget for TimelineTweetWidget<'_> {
                     .content(format!("\u{2606}{}", self.tweet.favourites_count))
                     .style(Style::default().fg(Color::Reset))
             },
+            Span::default().content(&spacing),
+            if self.tweet.bookmarked {
+                Span::default()
+                    .content("\u{2691}")
+                    .style(Style::default().fg(Color::Magenta))
+            } else {
+                Span::default().content("\u{2690}")
+            },
         ];
 
+        let bottom = area.top() + self.height() - 1;
         buf.set_line(
             area.left(),
-            area.top() + 2,
+            bottom - self.reactions_height(),
             &Line::default().spans(buttons.iter().cloned()),
             area.width,
         );
+        if self.reactions_height() > 0 {
+            buf.set_line(
+                area.left(),
+                bottom,
+                &reactions_line(&self.tweet.pleroma.emoji_reactions),
+                area.width,
+            );
+        }
     }
 }
 
 pub struct TimelineWidget<'a> {
     tweets: Vec<&'a Tweet>,
     i: usize,
+    previews: Option<&'a HashMap<String, Preview>>,
+    graphics: GraphicsProtocol,
+    content_warnings: Option<(&'a ContentWarningConfig, &'a Reveals)>,
+    absolute_time: bool,
 }
 
 impl<'a> TimelineWidget<'a> {
     pub fn new(i: usize, tweets: Vec<&'a Tweet>) -> Self {
-        TimelineWidget { tweets, i }
+        TimelineWidget {
+            tweets,
+            i,
+            previews: None,
+            graphics: GraphicsProtocol::HalfBlock,
+            content_warnings: None,
+            absolute_time: false,
+        }
+    }
+
+    /// For threads, where when matters more than how long ago
+    pub fn absolute_time(mut self) -> Self {
+        self.absolute_time = true;
+        self
+    }
+
+    pub fn content_warnings(
+        mut self,
+        config: &'a ContentWarningConfig,
+        reveals: &'a Reveals,
+    ) -> Self {
+        self.content_warnings = Some((config, reveals));
+        self
+    }
+
+    pub fn previews(
+        mut self,
+        previews: &'a HashMap<String, Preview>,
+        graphics: GraphicsProtocol,
+    ) -> Self {
+        self.previews = Some(previews);
+        self.graphics = graphics;
+        self
     }
 }
 
@@ -99,21 +520,49 @@ impl Widget for TimelineWidget<'_> {
     where
         Self: Sized,
     {
-        self.tweets
-            .iter()
-            .skip(self.i)
-            .take(area.height as usize / 4)
-            .map(|&tweet| TimelineTweetWidget::from(tweet))
-            .enumerate()
-            .for_each(|(i, tweet)| {
-                let tweet_area = Rect::new(area.x, area.y + 4 * i as u16, area.width, 3);
-                tweet.render(tweet_area, buf);
-                buf.set_string(
-                    area.x,
-                    area.y + 4 * i as u16 + 3,
-                    (0..area.width).map(|_| '-').collect::<String>(),
-                    Style::default(),
-                );
-            });
+        let mut y = area.y;
+        for tweet in self.tweets.iter().skip(self.i) {
+            let mut tweet = TimelineTweetWidget::from(*tweet).absolute_time(self.absolute_time);
+            if let Some(previews) = self.previews {
+                tweet = tweet.previews(previews, self.graphics);
+            }
+            if let Some((config, reveals)) = self.content_warnings {
+                tweet = tweet.content_warnings(config, reveals);
+            }
+            let height = tweet.height();
+            if y + height + 1 > area.bottom() {
+                break;
+            }
+            tweet.render(Rect::new(area.x, y, area.width, height), buf);
+            buf.set_string(
+                area.x,
+                y + height,
+                (0..area.width).map(|_| '-').collect::<String>(),
+                Style::default(),
+            );
+            y += height + 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ratatui::text::Span;
+
+    use super::truncate;
+
+    #[test]
+    fn truncate_by_cells() {
+        let spans = vec![Span::from("\u{732b}\u{732b} "), Span::from("@cat")];
+        let cut = |width| {
+            truncate(spans.clone(), width)
+                .iter()
+                .map(|s| s.content.to_string())
+                .collect::<String>()
+        };
+        assert_eq!(cut(9), "\u{732b}\u{732b} @cat");
+        // Wide characters aren't split
+        assert_eq!(cut(4), "\u{732b}\u{2026}");
+        assert_eq!(cut(7), "\u{732b}\u{732b} @\u{2026}");
     }
 }
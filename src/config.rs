@@ -0,0 +1,105 @@
+use std::{env::var, fs::read_to_string, io::ErrorKind, path::PathBuf};
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+/// An external program. The target gets appended to its arguments.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Opener {
+    pub command: String,
+    /// Takes over the terminal, like a terminal video player, so the
+    /// TUI has to be suspended until it exits.
+    #[serde(default)]
+    pub terminal: bool,
+}
+
+impl Opener {
+    fn new(command: &str) -> Self {
+        Opener {
+            command: command.to_string(),
+            terminal: false,
+        }
+    }
+
+    /// A program path taken as is, spaces included
+    fn program(path: &str) -> Self {
+        Opener::new(&format!("'{}'", path.replace('\'', r"'\''")))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct OpenConfig {
+    pub link: Opener,
+    pub image: Opener,
+    pub video: Opener,
+    pub audio: Opener,
+}
+
+impl Default for OpenConfig {
+    fn default() -> Self {
+        // $BROWSER is a colon separated list of programs, not a command line
+        let browser = var("BROWSER")
+            .ok()
+            .and_then(|list| list.split(':').find(|b| !b.is_empty()).map(Opener::program))
+            .unwrap_or(Opener::new("xdg-open"));
+        OpenConfig {
+            link: browser,
+            image: Opener::new("xdg-open"),
+            video: Opener::new("mpv"),
+            audio: Opener::new("mpv"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct DownloadConfig {
+    /// Where attachments are saved
+    pub directory: PathBuf,
+}
+
+impl Default for DownloadConfig {
+    fn default() -> Self {
+        let directory = dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_default()
+            .join("plerustax");
+        DownloadConfig { directory }
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ContentWarningConfig {
+    /// Never hide posts behind their CW
+    pub always_expand: bool,
+    /// Expand the CWs containing any of these words, case insensitive
+    pub expand_matching: Vec<String>,
+    /// Show media marked as sensitive without asking
+    pub show_sensitive_media: bool,
+}
+
+/// Settings read from `$XDG_CONFIG_HOME/plerustax/config.toml`
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Config {
+    pub open: OpenConfig,
+    pub download: DownloadConfig,
+    pub content_warnings: ContentWarningConfig,
+}
+
+impl Config {
+    /// Falls back to the defaults when there's no config file
+    pub fn load() -> Result<Self> {
+        let path = dirs::config_dir()
+            .ok_or(anyhow!("No config directory available"))?
+            .join("plerustax")
+            .join("config.toml");
+        match read_to_string(&path) {
+            Ok(data) => Ok(toml::from_str(&data)?),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
@@ -6,10 +6,12 @@ use std::{
 use anyhow::Result;
 use app::{App, input::input_generator, message::Message};
 use cli_log::init_cli_log;
+use config::Config;
 use pleroma::api::Api;
 use tokio::{sync::mpsc::Sender, task::JoinSet, time::sleep};
 
 mod app;
+mod config;
 mod pleroma;
 pub mod renderer;
 
@@ -20,6 +22,7 @@ const INSTANCE: &str = "https://cawfee.club";
 #[tokio::main]
 async fn main() -> Result<()> {
     init_cli_log!();
+    let config = Config::load()?;
     let mut buf = String::new();
     let mut api = Api::new(INSTANCE).await.unwrap();
 
@@ -37,7 +40,7 @@ async fn main() -> Result<()> {
     api.login(&username, &password).await?;
 
     let mut backend = api.backend().await;
-    let mut app = App::new(INSTANCE).await?;
+    let mut app = App::new(INSTANCE, config).await?;
     backend.register_app(app.send_end.clone()).await;
     app.register_backend(backend.send_end.clone()).await;
 
@@ -52,7 +55,7 @@ async fn main() -> Result<()> {
     threads.spawn(async move { backend.start().await });
     threads.spawn(async move { app.start().await });
     threads.spawn(start_tick_generator(tick_app));
-    threads.spawn(input_generator(input_app));
+    threads.spawn_blocking(move || input_generator(input_app));
 
     threads.join_all().await;
 
@@ -1,21 +1,72 @@
 use serde::Deserialize;
 
-#[derive(Deserialize, Debug)]
+use super::{
+    emoji::CustomEmoji,
+    lenient::{lenient_vec, null_default},
+};
+
+#[derive(Deserialize, Debug, Clone)]
 pub struct AccountField {
-    name: String,
-    value: String,
+    pub name: String,
+    pub value: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Account {
     pub id: String,
     pub acct: String,
+    #[serde(default, deserialize_with = "null_default")]
     pub display_name: String,
+    #[serde(default, deserialize_with = "null_default")]
     pub bot: bool,
+    /// Follows need approval
+    #[serde(default, deserialize_with = "null_default")]
+    pub locked: bool,
+    #[serde(default, deserialize_with = "null_default")]
     pub note: String,
+    #[serde(default, deserialize_with = "null_default")]
     pub url: String,
+    #[serde(default, deserialize_with = "null_default")]
     pub followers_count: u32,
+    #[serde(default, deserialize_with = "null_default")]
     pub following_count: u32,
+    #[serde(default, deserialize_with = "null_default")]
     pub statuses_count: u32,
+    #[serde(default, deserialize_with = "lenient_vec")]
     pub fields: Vec<AccountField>,
+    /// Custom emoji used in the display name, the bio and the fields
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub emojis: Vec<CustomEmoji>,
+    #[serde(default)]
+    pub pleroma: Option<AccountPleroma>,
+}
+
+impl Account {
+    pub fn is_admin(&self) -> bool {
+        self.pleroma.as_ref().is_some_and(|p| p.is_admin)
+    }
+
+    pub fn is_moderator(&self) -> bool {
+        self.pleroma.as_ref().is_some_and(|p| p.is_moderator)
+    }
+}
+
+/// How we relate to an account, empty when logged out
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Relationship {
+    pub following: bool,
+    pub followed_by: bool,
+    pub blocking: bool,
+    pub muting: bool,
+    pub requested: bool,
+}
+
+/// Fields only Pleroma and Akkoma send
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct AccountPleroma {
+    pub is_admin: bool,
+    pub is_moderator: bool,
+    pub relationship: Option<Relationship>,
 }
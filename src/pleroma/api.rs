@@ -1,14 +1,36 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::Path, time::Duration};
 
 use anyhow::{Result, anyhow};
-use reqwest::Client;
-use serde::Deserialize;
+use reqwest::{
+    Client, Response, StatusCode, Url,
+    header::{LINK, RANGE},
+    multipart::{Form, Part},
+};
+use serde::{Deserialize, de::DeserializeOwned};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt, time::sleep};
+use tokio_tungstenite::connect_async;
 
 use crate::app::backend::Backend;
 
-use super::{account::Account, tweet::Tweet};
+use super::{
+    account::Account,
+    chat::{Chat, ChatMessage, NewChatMessage},
+    conversation::Conversation,
+    emoji::CustomEmoji,
+    instance::Instance,
+    lenient::{lenient_vec, skip_malformed},
+    list::List,
+    media::MediaUpload,
+    scheduled::ScheduledTweet,
+    streaming::UserStream,
+    tag::{Hashtag, TagQuery},
+    tweet::{Context, NewTweet, Tweet, TweetRevision, TweetSource},
+};
 
-#[derive(Deserialize, Debug)]
+const MEDIA_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const MEDIA_POLL_ATTEMPTS: u32 = 60;
+
+#[derive(Deserialize, Debug, Clone)]
 struct CredentialApplication {
     client_id: String,
     client_secret: String,
@@ -21,11 +43,18 @@ struct TokenResponse {
 
 #[derive(Deserialize, Debug)]
 pub struct SearchResult {
+    #[serde(default, deserialize_with = "lenient_vec")]
     accounts: Vec<Account>,
+    #[serde(default, deserialize_with = "lenient_vec")]
     statuses: Vec<Tweet>,
 }
 
-#[derive(Debug)]
+#[derive(Deserialize)]
+struct UploadedMedia {
+    id: String,
+}
+
+#[derive(Debug, Clone)]
 pub struct Api {
     base_url: String,
     http: Client,
@@ -105,6 +134,31 @@ impl Api {
         Ok(())
     }
 
+    pub async fn verify_credentials(&self) -> Result<Account> {
+        let res = self
+            .http
+            .get(format!(
+                "{}/api/v1/accounts/verify_credentials",
+                self.base_url
+            ))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: Account = res.json().await?;
+        Ok(data)
+    }
+
     pub async fn home_timeline(&self, from_id: Option<&str>) -> Result<Vec<Tweet>> {
         let mut req = self
             .http
@@ -126,7 +180,7 @@ impl Api {
             ));
         }
 
-        let data: Vec<Tweet> = res.json().await?;
+        let data: Vec<Tweet> = skip_malformed(res.json().await?);
         Ok(data)
     }
 
@@ -152,7 +206,7 @@ impl Api {
             ));
         }
 
-        let data: Vec<Tweet> = res.json().await?;
+        let data: Vec<Tweet> = skip_malformed(res.json().await?);
         Ok(data)
     }
 
@@ -177,30 +231,105 @@ impl Api {
             ));
         }
 
-        let data: Vec<Tweet> = res.json().await?;
+        let data: Vec<Tweet> = skip_malformed(res.json().await?);
         Ok(data)
     }
 
-    pub async fn search_tweets(
+    /// Bookmarked posts, most recently bookmarked first, with the cursor
+    /// of the next page
+    pub async fn bookmarks(&self, max_id: Option<&str>) -> Result<(Vec<Tweet>, Option<String>)> {
+        self.paged("/api/v1/bookmarks", max_id).await
+    }
+
+    /// Posts we favourited, most recently favourited first, with the
+    /// cursor of the next page
+    pub async fn favourites(&self, max_id: Option<&str>) -> Result<(Vec<Tweet>, Option<String>)> {
+        self.paged("/api/v1/favourites", max_id).await
+    }
+
+    pub async fn favourited_by(
         &self,
-        search_term: &str,
-        offset: Option<u32>,
-    ) -> Result<SearchResult> {
-        let mut queries = vec![("q", search_term)];
-        let offset_str = offset.as_ref().unwrap_or(&0).to_string();
-        if !offset.is_some() {
-            queries.push(("offset", &offset_str));
+        id: &str,
+        max_id: Option<&str>,
+    ) -> Result<(Vec<Account>, Option<String>)> {
+        self.paged(&format!("/api/v1/statuses/{}/favourited_by", id), max_id)
+            .await
+    }
+
+    pub async fn reblogged_by(
+        &self,
+        id: &str,
+        max_id: Option<&str>,
+    ) -> Result<(Vec<Account>, Option<String>)> {
+        self.paged(&format!("/api/v1/statuses/{}/reblogged_by", id), max_id)
+            .await
+    }
+
+    /// Direct message threads, most recently active first, with the
+    /// cursor of the next page
+    pub async fn conversations(
+        &self,
+        max_id: Option<&str>,
+    ) -> Result<(Vec<Conversation>, Option<String>)> {
+        self.paged("/api/v1/conversations", max_id).await
+    }
+
+    pub async fn read_conversation(&self, id: &str) -> Result<Conversation> {
+        let res = self
+            .http
+            .post(format!(
+                "{}/api/v1/conversations/{}/read",
+                self.base_url, id
+            ))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
         }
-        let req = self
+
+        let data: Conversation = res.json().await?;
+        Ok(data)
+    }
+
+    /// Hides the conversation, its posts stay
+    pub async fn remove_conversation(&self, id: &str) -> Result<()> {
+        let res = self
             .http
-            .get(format!("{}/api/v2/search", self.base_url))
+            .delete(format!("{}/api/v1/conversations/{}", self.base_url, id))
             .header(
                 "Authorization",
                 format!("Bearer {}", self.token.as_ref().unwrap()),
             )
-            .query(&queries);
-        let res = req.send().await?;
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+        Ok(())
+    }
 
+    pub async fn lists(&self) -> Result<Vec<List>> {
+        let res = self
+            .http
+            .get(format!("{}/api/v1/lists", self.base_url))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
         if !res.status().is_success() {
             return Err(anyhow!(
                 "Status: {}\nMessage: {}",
@@ -209,25 +338,92 @@ impl Api {
             ));
         }
 
-        let data: SearchResult = res.json().await?;
+        Ok(skip_malformed(res.json().await?))
+    }
+
+    pub async fn create_list(&self, title: &str) -> Result<List> {
+        let res = self
+            .http
+            .post(format!("{}/api/v1/lists", self.base_url))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .json(&HashMap::from([("title", title)]))
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: List = res.json().await?;
         Ok(data)
     }
 
-    pub async fn post_tweet(&self, text: &str, visibility: &str) -> Result<()> {
-        let req = self
+    pub async fn rename_list(&self, id: &str, title: &str) -> Result<List> {
+        let res = self
             .http
-            .post(format!("{}/api/v1/statuses", self.base_url))
+            .put(format!("{}/api/v1/lists/{}", self.base_url, id))
             .header(
                 "Authorization",
                 format!("Bearer {}", self.token.as_ref().unwrap()),
-            );
-        let mut params = HashMap::new();
-        params.insert("status", text);
-        params.insert("content_type", "text/plain");
-        params.insert("source", "plerustax");
-        params.insert("visibility", visibility);
+            )
+            .json(&HashMap::from([("title", title)]))
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: List = res.json().await?;
+        Ok(data)
+    }
+
+    pub async fn delete_list(&self, id: &str) -> Result<()> {
+        let res = self
+            .http
+            .delete(format!("{}/api/v1/lists/{}", self.base_url, id))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+        Ok(())
+    }
 
-        let res = req.json(&params).send().await?;
+    /// Adds the account to the list if `add`, removes it otherwise.
+    /// Mastodon only takes accounts we follow.
+    pub async fn edit_list_account(&self, id: &str, account_id: &str, add: bool) -> Result<()> {
+        let url = format!("{}/api/v1/lists/{}/accounts", self.base_url, id);
+        let req = if add {
+            self.http.post(url)
+        } else {
+            self.http.delete(url)
+        };
+        let res = req
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .json(&HashMap::from([("account_ids", [account_id])]))
+            .send()
+            .await?;
         if !res.status().is_success() {
             return Err(anyhow!(
                 "Status: {}\nMessage: {}",
@@ -238,10 +434,57 @@ impl Api {
         Ok(())
     }
 
-    pub async fn delete_tweet(&self, id: &str) -> Result<()> {
+    /// Posts of the accounts in the list, with the cursor of older ones
+    pub async fn list_timeline(
+        &self,
+        id: &str,
+        max_id: Option<&str>,
+    ) -> Result<(Vec<Tweet>, Option<String>)> {
+        self.paged(&format!("/api/v1/timelines/list/{}", id), max_id)
+            .await
+    }
+
+    /// Posts with the hashtag, narrowed down by the rest of the query
+    pub async fn tag_timeline(
+        &self,
+        query: &TagQuery,
+        max_id: Option<&str>,
+    ) -> Result<(Vec<Tweet>, Option<String>)> {
+        self.paged_with(
+            &format!("/api/v1/timelines/tag/{}", query.tag),
+            &query.params(),
+            max_id,
+        )
+        .await
+    }
+
+    pub async fn followed_tags(
+        &self,
+        max_id: Option<&str>,
+    ) -> Result<(Vec<Hashtag>, Option<String>)> {
+        self.paged("/api/v1/followed_tags", max_id).await
+    }
+
+    /// Every page of [`Api::followed_tags`]
+    pub async fn all_followed_tags(&self) -> Result<Vec<Hashtag>> {
+        let mut tags = Vec::new();
+        let mut max_id = None;
+        loop {
+            let (page, next) = self.followed_tags(max_id.as_deref()).await?;
+            tags.extend(page);
+            match next {
+                Some(next) => max_id = Some(next),
+                None => return Ok(tags),
+            }
+        }
+    }
+
+    /// Follows the hashtag if `follow`, unfollows it otherwise
+    pub async fn follow_tag(&self, name: &str, follow: bool) -> Result<Hashtag> {
+        let action = if follow { "follow" } else { "unfollow" };
         let res = self
             .http
-            .delete(format!("{}/api/v1/statuses/{}", self.base_url, id))
+            .post(format!("{}/api/v1/tags/{}/{}", self.base_url, name, action))
             .header(
                 "Authorization",
                 format!("Bearer {}", self.token.as_ref().unwrap()),
@@ -255,21 +498,734 @@ impl Api {
                 res.text().await?
             ));
         }
-        Ok(())
+
+        let data: Hashtag = res.json().await?;
+        Ok(data)
     }
 
-    pub async fn backend(self) -> Backend {
-        Backend::new(self).await
+    /// Our Pleroma chats, most recently active first
+    pub async fn chats(&self) -> Result<Vec<Chat>> {
+        let res = self
+            .http
+            .get(format!("{}/api/v1/pleroma/chats", self.base_url))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        Ok(skip_malformed(res.json().await?))
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::Api;
+    /// The chat with an account, created if there's none yet
+    pub async fn chat_by_account(&self, account_id: &str) -> Result<Chat> {
+        let res = self
+            .http
+            .post(format!(
+                "{}/api/v1/pleroma/chats/by-account/{}",
+                self.base_url, account_id
+            ))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
 
-    #[tokio::test]
-    async fn new_backend() {
-        let b = Api::new("https://cawfee.club").await.unwrap();
-        assert!(b.credentials.is_some());
+        let data: Chat = res.json().await?;
+        Ok(data)
+    }
+
+    /// Messages of a chat, newest first, with the cursor of older ones
+    pub async fn chat_messages(
+        &self,
+        chat_id: &str,
+        max_id: Option<&str>,
+    ) -> Result<(Vec<ChatMessage>, Option<String>)> {
+        self.paged(
+            &format!("/api/v1/pleroma/chats/{}/messages", chat_id),
+            max_id,
+        )
+        .await
+    }
+
+    pub async fn send_chat_message(
+        &self,
+        chat_id: &str,
+        message: &NewChatMessage,
+    ) -> Result<ChatMessage> {
+        let res = self
+            .http
+            .post(format!(
+                "{}/api/v1/pleroma/chats/{}/messages",
+                self.base_url, chat_id
+            ))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .json(message)
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: ChatMessage = res.json().await?;
+        Ok(data)
+    }
+
+    /// Marks the messages up to `last_read_id` as read
+    pub async fn read_chat(&self, chat_id: &str, last_read_id: &str) -> Result<Chat> {
+        let res = self
+            .http
+            .post(format!(
+                "{}/api/v1/pleroma/chats/{}/read",
+                self.base_url, chat_id
+            ))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .json(&HashMap::from([("last_read_id", last_read_id)]))
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: Chat = res.json().await?;
+        Ok(data)
+    }
+
+    /// Opens the `user` stream of the streaming API
+    pub async fn user_stream(&self) -> Result<UserStream> {
+        let mut url = Url::parse(&self.base_url)?;
+        let scheme = if url.scheme() == "http" { "ws" } else { "wss" };
+        url.set_scheme(scheme)
+            .map_err(|_| anyhow!("Can't stream from {}", self.base_url))?;
+        url.set_path("/api/v1/streaming");
+        url.query_pairs_mut()
+            .append_pair("access_token", self.token.as_ref().unwrap())
+            .append_pair("stream", "user");
+        let (socket, _) = connect_async(url.as_str()).await?;
+        Ok(UserStream::new(socket))
+    }
+
+    /// For the endpoints paging by their own ids, not the ones of the
+    /// items, so the cursor comes from the `Link` header
+    async fn paged<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        max_id: Option<&str>,
+    ) -> Result<(Vec<T>, Option<String>)> {
+        self.paged_with(path, &[], max_id).await
+    }
+
+    /// [`Api::paged`] with more query parameters
+    async fn paged_with<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &[(&str, &str)],
+        max_id: Option<&str>,
+    ) -> Result<(Vec<T>, Option<String>)> {
+        let mut req = self
+            .http
+            .get(format!("{}{}", self.base_url, path))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .query(params);
+        if let Some(max_id) = max_id {
+            req = req.query(&[("max_id", max_id)]);
+        }
+        let res = req.send().await?;
+
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let next = next_page(&res);
+        let data: Vec<T> = skip_malformed(res.json().await?);
+        Ok((data, next))
+    }
+
+    /// Bookmarks the post if `bookmark`, removes the bookmark otherwise
+    pub async fn bookmark_tweet(&self, id: &str, bookmark: bool) -> Result<Tweet> {
+        let action = if bookmark { "bookmark" } else { "unbookmark" };
+        let res = self
+            .http
+            .post(format!(
+                "{}/api/v1/statuses/{}/{}",
+                self.base_url, id, action
+            ))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: Tweet = res.json().await?;
+        Ok(data)
+    }
+
+    /// Adds our reaction with `emoji` to the post, or removes it. Custom
+    /// emoji go by their shortcode.
+    pub async fn react_tweet(&self, id: &str, emoji: &str, react: bool) -> Result<Tweet> {
+        let mut url = Url::parse(&format!(
+            "{}/api/v1/pleroma/statuses/{}/reactions",
+            self.base_url, id
+        ))?;
+        // Encodes the emoji
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Invalid instance url"))?
+            .push(emoji);
+        let req = if react {
+            self.http.put(url)
+        } else {
+            self.http.delete(url)
+        };
+        let res = req
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: Tweet = res.json().await?;
+        Ok(data)
+    }
+
+    pub async fn custom_emojis(&self) -> Result<Vec<CustomEmoji>> {
+        let res = self
+            .http
+            .get(format!("{}/api/v1/custom_emojis", self.base_url))
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: Vec<CustomEmoji> = skip_malformed(res.json().await?);
+        Ok(data)
+    }
+
+    pub async fn search_tweets(
+        &self,
+        search_term: &str,
+        offset: Option<u32>,
+    ) -> Result<SearchResult> {
+        let mut queries = vec![("q", search_term)];
+        let offset_str = offset.as_ref().unwrap_or(&0).to_string();
+        if !offset.is_some() {
+            queries.push(("offset", &offset_str));
+        }
+        let req = self
+            .http
+            .get(format!("{}/api/v2/search", self.base_url))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .query(&queries);
+        let res = req.send().await?;
+
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: SearchResult = res.json().await?;
+        Ok(data)
+    }
+
+    pub async fn post_tweet(&self, tweet: &NewTweet) -> Result<()> {
+        let req = self
+            .http
+            .post(format!("{}/api/v1/statuses", self.base_url))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            );
+
+        let res = req.json(tweet).send().await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn instance(&self) -> Result<Instance> {
+        let res = self
+            .http
+            .get(format!("{}/api/v1/instance", self.base_url))
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: Instance = res.json().await?;
+        Ok(data)
+    }
+
+    /// Uploads the file and waits for the server to finish processing it.
+    /// Returns the id to use in `NewTweet::media_ids`.
+    pub async fn upload_media(&self, media: &MediaUpload) -> Result<String> {
+        let file_name = media
+            .path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("upload")
+            .to_string();
+        let content = tokio::fs::read(&media.path).await?;
+        let mut form = Form::new().part(
+            "file",
+            Part::bytes(content)
+                .file_name(file_name)
+                .mime_str(media.mime_type())?,
+        );
+        if !media.description.is_empty() {
+            form = form.text("description", media.description.clone());
+        }
+        if let Some((x, y)) = media.focus {
+            form = form.text("focus", format!("{},{}", x, y));
+        }
+
+        let res = self
+            .http
+            .post(format!("{}/api/v2/media", self.base_url))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .multipart(form)
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        // 202 means the server is still processing the file asynchronously
+        let mut processed = res.status() != StatusCode::ACCEPTED;
+        let data: UploadedMedia = res.json().await?;
+        let mut attempts = 0;
+        while !processed {
+            if attempts == MEDIA_POLL_ATTEMPTS {
+                return Err(anyhow!("Media {} is taking too long to process", data.id));
+            }
+            sleep(MEDIA_POLL_INTERVAL).await;
+            processed = self.media_processed(&data.id).await?;
+            attempts += 1;
+        }
+        Ok(data.id)
+    }
+
+    async fn media_processed(&self, id: &str) -> Result<bool> {
+        let res = self
+            .http
+            .get(format!("{}/api/v1/media/{}", self.base_url, id))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+        Ok(res.status() != StatusCode::PARTIAL_CONTENT)
+    }
+
+    pub async fn tweet(&self, id: &str) -> Result<Tweet> {
+        let res = self
+            .http
+            .get(format!("{}/api/v1/statuses/{}", self.base_url, id))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: Tweet = res.json().await?;
+        Ok(data)
+    }
+
+    pub async fn context(&self, id: &str) -> Result<Context> {
+        let res = self
+            .http
+            .get(format!("{}/api/v1/statuses/{}/context", self.base_url, id))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: Context = res.json().await?;
+        Ok(data)
+    }
+
+    /// Posts waiting to be published, soonest first. There are few of
+    /// them, so all pages are fetched to sort them.
+    pub async fn scheduled_tweets(&self) -> Result<Vec<ScheduledTweet>> {
+        let mut data: Vec<ScheduledTweet> = Vec::new();
+        let mut max_id = None;
+        loop {
+            let (page, next) = self
+                .paged("/api/v1/scheduled_statuses", max_id.as_deref())
+                .await?;
+            data.extend(page);
+            match next {
+                Some(next) => max_id = Some(next),
+                None => break,
+            }
+        }
+        data.sort_by(|a, b| a.scheduled_at.cmp(&b.scheduled_at));
+        Ok(data)
+    }
+
+    pub async fn reschedule_tweet(&self, id: &str, scheduled_at: &str) -> Result<ScheduledTweet> {
+        let res = self
+            .http
+            .put(format!(
+                "{}/api/v1/scheduled_statuses/{}",
+                self.base_url, id
+            ))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .json(&HashMap::from([("scheduled_at", scheduled_at)]))
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: ScheduledTweet = res.json().await?;
+        Ok(data)
+    }
+
+    pub async fn cancel_scheduled_tweet(&self, id: &str) -> Result<()> {
+        let res = self
+            .http
+            .delete(format!(
+                "{}/api/v1/scheduled_statuses/{}",
+                self.base_url, id
+            ))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+        Ok(())
+    }
+
+    /// Replaces the content of one of our posts, the visibility and the
+    /// reply target of `tweet` are ignored
+    pub async fn edit_tweet(&self, id: &str, tweet: &NewTweet) -> Result<Tweet> {
+        let res = self
+            .http
+            .put(format!("{}/api/v1/statuses/{}", self.base_url, id))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .json(tweet)
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: Tweet = res.json().await?;
+        Ok(data)
+    }
+
+    pub async fn tweet_source(&self, id: &str) -> Result<TweetSource> {
+        let res = self
+            .http
+            .get(format!("{}/api/v1/statuses/{}/source", self.base_url, id))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: TweetSource = res.json().await?;
+        Ok(data)
+    }
+
+    /// Every version of the post, oldest first
+    pub async fn tweet_history(&self, id: &str) -> Result<Vec<TweetRevision>> {
+        let res = self
+            .http
+            .get(format!("{}/api/v1/statuses/{}/history", self.base_url, id))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: Vec<TweetRevision> = skip_malformed(res.json().await?);
+        Ok(data)
+    }
+
+    /// Returns the deleted post with its source `text`, to redraft it
+    pub async fn delete_tweet(&self, id: &str) -> Result<Tweet> {
+        let res = self
+            .http
+            .delete(format!("{}/api/v1/statuses/{}", self.base_url, id))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.as_ref().unwrap()),
+            )
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        let data: Tweet = res.json().await?;
+        Ok(data)
+    }
+
+    /// Fetches a file, like media previews
+    pub async fn download(&self, url: &str) -> Result<Vec<u8>> {
+        let res = self.http.get(url).send().await?;
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+        Ok(res.bytes().await?.to_vec())
+    }
+
+    /// Saves a file to `path`. Data goes to `<path>.part` first, so an
+    /// interrupted download is resumed from there on the next attempt.
+    /// `progress` gets the bytes written so far and the total if known.
+    pub async fn download_to(
+        &self,
+        url: &str,
+        path: &Path,
+        mut progress: impl FnMut(u64, Option<u64>),
+    ) -> Result<()> {
+        if path.exists() {
+            return Ok(());
+        }
+        let mut part = path.as_os_str().to_owned();
+        part.push(".part");
+        let part = Path::new(&part);
+
+        let mut downloaded = tokio::fs::metadata(part)
+            .await
+            .map(|m| m.len())
+            .unwrap_or(0);
+        let mut req = self.http.get(url);
+        if downloaded > 0 {
+            req = req.header(RANGE, format!("bytes={}-", downloaded));
+        }
+        let mut res = req.send().await?;
+        // The partial file was already complete
+        if res.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            tokio::fs::rename(part, path).await?;
+            return Ok(());
+        }
+        if !res.status().is_success() {
+            return Err(anyhow!(
+                "Status: {}\nMessage: {}",
+                res.status().as_u16(),
+                res.text().await?
+            ));
+        }
+
+        // Servers ignoring the range send the whole file again
+        let resumed = res.status() == StatusCode::PARTIAL_CONTENT;
+        if !resumed {
+            downloaded = 0;
+        }
+        let size = res.content_length().map(|l| l + downloaded);
+        let mut file = if resumed {
+            OpenOptions::new().append(true).open(part).await?
+        } else {
+            OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(part)
+                .await?
+        };
+        while let Some(chunk) = res.chunk().await? {
+            file.write_all(&chunk).await?;
+            downloaded += chunk.len() as u64;
+            progress(downloaded, size);
+        }
+        file.flush().await?;
+        tokio::fs::rename(part, path).await?;
+        Ok(())
+    }
+
+    pub async fn backend(self) -> Backend {
+        Backend::new(self).await
+    }
+}
+
+/// `max_id` of the `rel="next"` link in the `Link` header, for the
+/// endpoints that don't page by post id
+fn next_page(res: &Response) -> Option<String> {
+    next_max_id(res.headers().get(LINK)?.to_str().ok()?)
+}
+
+fn next_max_id(link: &str) -> Option<String> {
+    link.split(',')
+        .find(|l| l.contains("rel=\"next\""))
+        .and_then(|l| {
+            let url = l.split(['<', '>']).nth(1)?;
+            url.split(['?', '&'])
+                .find_map(|param| param.strip_prefix("max_id="))
+                .map(|id| id.to_string())
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Api, next_max_id};
+
+    #[tokio::test]
+    async fn new_backend() {
+        let b = Api::new("https://cawfee.club").await.unwrap();
+        assert!(b.credentials.is_some());
+    }
+
+    #[test]
+    fn link_header_next_page() {
+        let link = "<https://example.com/api/v1/bookmarks?limit=20&max_id=42>; rel=\"next\", \
+                    <https://example.com/api/v1/bookmarks?limit=20&min_id=50>; rel=\"prev\"";
+        assert_eq!(next_max_id(link), Some("42".to_string()));
+        assert_eq!(
+            next_max_id("<https://example.com/?min_id=1>; rel=\"prev\""),
+            None
+        );
     }
 }
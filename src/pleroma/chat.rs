@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    account::Account,
+    emoji::CustomEmoji,
+    lenient::{lenient_vec, null_default},
+    tweet::MediaAttatchment,
+};
+
+/// A Pleroma chat with one account
+#[derive(Deserialize, Debug, Clone)]
+pub struct Chat {
+    pub id: String,
+    /// The other side
+    pub account: Account,
+    #[serde(default, deserialize_with = "null_default")]
+    pub unread: u32,
+    #[serde(default)]
+    pub last_message: Option<ChatMessage>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ChatMessage {
+    pub id: String,
+    pub chat_id: String,
+    pub account_id: String,
+    /// HTML, `null` for messages with only an attachment
+    #[serde(default, deserialize_with = "null_default")]
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub attachment: Option<MediaAttatchment>,
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub emojis: Vec<CustomEmoji>,
+    /// Not read by us yet
+    #[serde(default)]
+    pub unread: bool,
+}
+
+/// A message to send in a chat
+#[derive(Serialize, Debug)]
+pub struct NewChatMessage {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_id: Option<String>,
+}
@@ -0,0 +1,20 @@
+use serde::Deserialize;
+
+use super::{
+    account::Account,
+    lenient::{lenient_vec, null_default},
+    tweet::Tweet,
+};
+
+/// Direct messages between us and some accounts
+#[derive(Deserialize, Debug, Clone)]
+pub struct Conversation {
+    pub id: String,
+    #[serde(default, deserialize_with = "null_default")]
+    pub unread: bool,
+    /// Everyone in it but us
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub accounts: Vec<Account>,
+    #[serde(default)]
+    pub last_status: Option<Tweet>,
+}
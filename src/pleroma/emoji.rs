@@ -0,0 +1,30 @@
+use serde::Deserialize;
+
+/// An emoji of the instance, used as `:shortcode:`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct CustomEmoji {
+    pub shortcode: String,
+    pub url: String,
+    pub static_url: String,
+    #[serde(default = "visible")]
+    pub visible_in_picker: bool,
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+fn visible() -> bool {
+    true
+}
+
+/// Reactions with the same emoji on a post
+#[derive(Deserialize, Debug, Clone)]
+pub struct EmojiReaction {
+    /// The emoji itself, or the shortcode without colons for custom ones
+    pub name: String,
+    pub count: u32,
+    /// Whether we reacted with it
+    pub me: bool,
+    /// Image of custom emoji
+    #[serde(default)]
+    pub url: Option<String>,
+}
@@ -0,0 +1,108 @@
+/// Text of the HTML content, tags dropped and paragraphs kept apart
+pub fn strip_html(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('>')
+            .map(|e| start + e + 1)
+            .unwrap_or(rest.len());
+        let tag = &rest[start..end];
+        if tag.starts_with("<br") || tag.starts_with("</p") {
+            text.push('\n');
+        }
+        rest = &rest[end..];
+    }
+    text.push_str(rest);
+    decode_entities(&text)
+}
+
+/// Value of an attribute of an HTML start tag, quoted or not, with the
+/// entities decoded
+pub fn attribute(tag: &str, name: &str) -> Option<String> {
+    // Past the tag name
+    let mut rest = &tag[tag.find(char::is_whitespace)?..];
+    loop {
+        rest = rest.trim_start();
+        let key_len = rest
+            .find(|c: char| c == '=' || c == '>' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        if key_len == 0 {
+            return None;
+        }
+        let key = &rest[..key_len];
+        rest = rest[key_len..].trim_start();
+        let mut value = "";
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (v, left) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let end = after[1..].find(quote)? + 1;
+                    (&after[1..end], &after[end + 1..])
+                }
+                _ => {
+                    let end = after
+                        .find(|c: char| c == '>' || c.is_whitespace())
+                        .unwrap_or(after.len());
+                    after.split_at(end)
+                }
+            };
+            value = v;
+            rest = left;
+        }
+        if key.eq_ignore_ascii_case(name) {
+            return Some(decode_entities(value));
+        }
+    }
+}
+
+/// Named entities servers escape attributes with, and numeric ones
+pub fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest.find(';').map(|end| (&rest[1..end], end));
+        let c = entity.and_then(|(entity, _)| match entity {
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            _ => entity
+                .strip_prefix("#x")
+                .or(entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|n| n.parse().ok()))
+                .and_then(char::from_u32),
+        });
+        match (c, entity) {
+            (Some(c), Some((_, end))) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            // A lone ampersand
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::strip_html;
+
+    #[test]
+    fn strip_tags_and_entities() {
+        assert_eq!(
+            strip_html("<p>a &lt;b&gt; <a href=\"x\">link</a><br/>&amp;lt; &#8230;</p><p>c</p>"),
+            "a <b> link\n&lt; \u{2026}\nc\n"
+        );
+    }
+}
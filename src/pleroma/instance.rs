@@ -0,0 +1,191 @@
+use std::fs::metadata;
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+use super::{media::MediaUpload, tweet::ContentType};
+
+/// Mastodon's limit, used when the instance doesn't advertise one
+const DEFAULT_MAX_MEDIA_ATTACHMENTS: usize = 4;
+
+#[derive(Deserialize, Debug, Default)]
+pub struct StatusesConfiguration {
+    pub max_characters: Option<u32>,
+    pub max_media_attachments: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct MediaConfiguration {
+    #[serde(default)]
+    pub supported_mime_types: Vec<String>,
+    pub image_size_limit: Option<u64>,
+    pub video_size_limit: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct InstanceConfiguration {
+    #[serde(default)]
+    pub statuses: StatusesConfiguration,
+    #[serde(default)]
+    pub media_attachments: MediaConfiguration,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct PleromaMetadata {
+    /// Mime types accepted as `content_type` when posting
+    #[serde(default)]
+    pub post_formats: Vec<String>,
+}
+
+/// Pleroma and Akkoma extensions
+#[derive(Deserialize, Debug, Default)]
+pub struct InstancePleroma {
+    #[serde(default)]
+    pub metadata: PleromaMetadata,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Instance {
+    pub uri: String,
+    pub title: String,
+    /// Pleroma only, applies to every kind of upload
+    pub upload_limit: Option<u64>,
+    /// Pleroma only, Mastodon puts it in `configuration`
+    pub max_media_attachments: Option<usize>,
+    #[serde(default)]
+    pub configuration: InstanceConfiguration,
+    pub pleroma: Option<InstancePleroma>,
+}
+
+impl Instance {
+    pub fn max_media_attachments(&self) -> usize {
+        self.configuration
+            .statuses
+            .max_media_attachments
+            .or(self.max_media_attachments)
+            .unwrap_or(DEFAULT_MAX_MEDIA_ATTACHMENTS)
+    }
+
+    /// Formats the instance can render, only plain text if it
+    /// doesn't say, like Mastodon.
+    pub fn post_formats(&self) -> Vec<ContentType> {
+        let formats = self
+            .pleroma
+            .iter()
+            .flat_map(|p| p.metadata.post_formats.iter())
+            .filter_map(|f| ContentType::from_mime(f))
+            .collect::<Vec<ContentType>>();
+        if formats.is_empty() {
+            vec![ContentType::PlainText]
+        } else {
+            formats
+        }
+    }
+
+    /// Size limit in bytes for an upload of the given mime type, if any
+    pub fn size_limit(&self, mime: &str) -> Option<u64> {
+        let media = &self.configuration.media_attachments;
+        let limit = if mime.starts_with("video/") {
+            media.video_size_limit
+        } else if mime.starts_with("image/") {
+            media.image_size_limit
+        } else {
+            None
+        };
+        limit.or(self.upload_limit)
+    }
+
+    /// Makes sure the attachments can be uploaded before wasting
+    /// bandwidth on a request the server is going to refuse. `kept` are
+    /// the ones already on the server that the post keeps.
+    pub fn check_attachments(&self, kept: usize, attachments: &[MediaUpload]) -> Result<()> {
+        let max = self.max_media_attachments();
+        if kept + attachments.len() > max {
+            return Err(anyhow!("Too many attachments, the limit is {}", max));
+        }
+
+        let supported = &self.configuration.media_attachments.supported_mime_types;
+        for attachment in attachments {
+            let mime = attachment.mime_type();
+            if !supported.is_empty() && !supported.iter().any(|m| m == mime) {
+                return Err(anyhow!(
+                    "{}: {} is not supported by the instance",
+                    attachment.path.display(),
+                    mime
+                ));
+            }
+
+            let size = metadata(&attachment.path)?.len();
+            if let Some(limit) = self.size_limit(mime)
+                && size > limit
+            {
+                return Err(anyhow!(
+                    "{}: {} bytes exceeds the limit of {} bytes",
+                    attachment.path.display(),
+                    size,
+                    limit
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        env::temp_dir,
+        fs::{create_dir_all, remove_dir_all, write},
+        process,
+        slice::from_ref,
+    };
+
+    use crate::pleroma::media::MediaUpload;
+
+    use super::Instance;
+
+    fn instance(upload_limit: Option<u64>, max_media_attachments: Option<usize>) -> Instance {
+        Instance {
+            uri: "example.com".to_string(),
+            title: "Example".to_string(),
+            upload_limit,
+            max_media_attachments,
+            configuration: Default::default(),
+            pleroma: None,
+        }
+    }
+
+    #[test]
+    fn attachment_limits() {
+        // Unique per process, concurrent test runs don't share the file
+        let dir = temp_dir().join(format!("plerustax-attachment-limits-{}", process::id()));
+        create_dir_all(&dir).unwrap();
+        let path = dir.join("upload.png");
+        write(&path, [0; 16]).unwrap();
+        let upload = MediaUpload::new(path.clone());
+
+        assert!(
+            instance(None, None)
+                .check_attachments(0, from_ref(&upload))
+                .is_ok()
+        );
+        assert!(
+            instance(Some(8), None)
+                .check_attachments(0, from_ref(&upload))
+                .is_err()
+        );
+        // Attachments kept by an edit count too
+        assert!(
+            instance(None, Some(1))
+                .check_attachments(1, from_ref(&upload))
+                .is_err()
+        );
+        assert!(
+            instance(None, Some(1))
+                .check_attachments(0, &[upload.clone(), upload])
+                .is_err()
+        );
+        assert_eq!(instance(None, None).max_media_attachments(), 4);
+        remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,39 @@
+use cli_log::warn;
+use serde::{Deserialize, Deserializer, de::DeserializeOwned};
+use serde_json::Value;
+
+/// For `#[serde(default, deserialize_with = "null_default")]`, turns
+/// `null` into the default as well
+pub fn null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// For `#[serde(default, deserialize_with = "lenient_vec")]`, drops the
+/// items that don't deserialize instead of failing the whole object
+pub fn lenient_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let items = Option::<Vec<Value>>::deserialize(deserializer)?.unwrap_or_default();
+    Ok(skip_malformed(items))
+}
+
+/// Deserializes each item on its own, logging and skipping the ones that
+/// fail, so one odd status doesn't lose a whole timeline page
+pub fn skip_malformed<T: DeserializeOwned>(items: Vec<Value>) -> Vec<T> {
+    items
+        .into_iter()
+        .filter_map(|item| match serde_json::from_value(item) {
+            Ok(item) => Some(item),
+            Err(e) => {
+                warn!("Skipping malformed item: {}", e);
+                None
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+/// A list of accounts whose posts make up a timeline
+#[derive(Deserialize, Debug, Clone)]
+pub struct List {
+    pub id: String,
+    pub title: String,
+}
@@ -0,0 +1,50 @@
+use std::path::{Path, PathBuf};
+
+/// A local file waiting to be uploaded as an attachment
+#[derive(Clone, Debug)]
+pub struct MediaUpload {
+    pub path: PathBuf,
+    /// Alt text shown to people who can't see the media
+    pub description: String,
+    /// Focal point in the `-1.0..=1.0` range used by the server to crop previews
+    pub focus: Option<(f32, f32)>,
+}
+
+impl MediaUpload {
+    pub fn new(path: PathBuf) -> Self {
+        MediaUpload {
+            path,
+            description: String::new(),
+            focus: None,
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        mime_type(&self.path)
+    }
+}
+
+/// Guesses the mime type from the file extension
+pub fn mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("avif") => "image/avif",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("mov") => "video/quicktime",
+        Some("mp3") => "audio/mpeg",
+        Some("ogg") | Some("oga") => "audio/ogg",
+        Some("opus") => "audio/opus",
+        Some("flac") => "audio/flac",
+        Some("wav") => "audio/wav",
+        Some("m4a") => "audio/mp4",
+        _ => "application/octet-stream",
+    }
+}
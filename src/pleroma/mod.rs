@@ -1,3 +1,14 @@
-mod account;
+pub mod account;
 pub mod api;
+pub mod chat;
+pub mod conversation;
+pub mod emoji;
+pub mod html;
+pub mod instance;
+pub mod lenient;
+pub mod list;
+pub mod media;
+pub mod scheduled;
+pub mod streaming;
+pub mod tag;
 pub mod tweet;
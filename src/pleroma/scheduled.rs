@@ -0,0 +1,21 @@
+use serde::Deserialize;
+
+use super::{lenient::lenient_vec, tweet::MediaAttatchment};
+
+/// What the post will be published with
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScheduledParams {
+    pub text: String,
+    #[serde(default)]
+    pub spoiler_text: Option<String>,
+}
+
+/// A post waiting on the server to be published at `scheduled_at`
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScheduledTweet {
+    pub id: String,
+    pub scheduled_at: String,
+    pub params: ScheduledParams,
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub media_attachments: Vec<MediaAttatchment>,
+}
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use cli_log::warn;
+use futures_util::StreamExt;
+use serde::Deserialize;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, tungstenite};
+
+use super::chat::Chat;
+
+/// What we follow of the user stream
+#[derive(Debug)]
+pub enum StreamEvent {
+    ChatUpdate(Chat),
+}
+
+/// Frames of the streaming API, the payload being JSON in a string
+#[derive(Deserialize)]
+struct Frame {
+    event: String,
+    #[serde(default)]
+    payload: String,
+}
+
+impl StreamEvent {
+    /// `None` for the events we ignore
+    pub fn parse(text: &str) -> Result<Option<Self>> {
+        let frame: Frame = serde_json::from_str(text)?;
+        Ok(match frame.event.as_str() {
+            "pleroma:chat_update" => Some(StreamEvent::ChatUpdate(serde_json::from_str(
+                &frame.payload,
+            )?)),
+            _ => None,
+        })
+    }
+}
+
+/// Connection to `/api/v1/streaming` for the `user` stream
+pub struct UserStream {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl UserStream {
+    pub fn new(socket: WebSocketStream<MaybeTlsStream<TcpStream>>) -> Self {
+        UserStream { socket }
+    }
+
+    /// Waits for the next event we follow, `None` once the connection
+    /// is closed
+    pub async fn next(&mut self) -> Option<StreamEvent> {
+        while let Some(message) = self.socket.next().await {
+            let text = match message {
+                Ok(tungstenite::Message::Text(text)) => text,
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!("User stream failed: {}", e);
+                    return None;
+                }
+            };
+            match StreamEvent::parse(&text) {
+                Ok(Some(event)) => return Some(event),
+                Ok(None) => (),
+                Err(e) => warn!("Skipping malformed stream event: {}", e),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StreamEvent;
+
+    #[test]
+    fn chat_update() {
+        let payload = r#"{"id":"1","unread":2,"account":{"id":"9","acct":"bob"},"last_message":{"id":"5","chat_id":"1","account_id":"9","content":"hi","created_at":"2024-06-11T21:03:00.000Z","attachment":null}}"#;
+        let frame = serde_json::json!({"event": "pleroma:chat_update", "payload": payload});
+        match StreamEvent::parse(&frame.to_string()).unwrap() {
+            Some(StreamEvent::ChatUpdate(chat)) => {
+                assert_eq!(chat.unread, 2);
+                assert_eq!(chat.last_message.unwrap().content, "hi");
+            }
+            None => panic!("chat update ignored"),
+        }
+        let update = r#"{"event":"update","payload":"{}"}"#;
+        assert!(StreamEvent::parse(update).unwrap().is_none());
+    }
+}
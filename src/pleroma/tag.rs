@@ -0,0 +1,137 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::anyhow;
+use serde::Deserialize;
+
+use super::lenient::null_default;
+
+/// A hashtag as the tags API returns it
+#[derive(Deserialize, Debug, Clone)]
+pub struct Hashtag {
+    pub name: String,
+    /// Whether we follow it, only known to the tags API
+    #[serde(default, deserialize_with = "null_default")]
+    pub following: bool,
+}
+
+/// What a hashtag timeline shows, written like
+/// `#rust any:zig,golang all:tui none:nsfw local`. Hashtags aren't case
+/// sensitive, so they're kept lowercase to compare queries.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TagQuery {
+    pub tag: String,
+    /// Posts with any of these also show up
+    pub any: Vec<String>,
+    /// Posts must have all of these too
+    pub all: Vec<String>,
+    /// Posts with any of these are left out
+    pub none: Vec<String>,
+    /// Just posts from our instance
+    pub local: bool,
+}
+
+fn is_hashtag(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn hashtags(list: &str) -> anyhow::Result<Vec<String>> {
+    list.split(',')
+        .map(|name| {
+            let name = name.trim_start_matches('#');
+            if is_hashtag(name) {
+                Ok(name.to_lowercase())
+            } else {
+                Err(anyhow!("Not a hashtag: {}", name))
+            }
+        })
+        .collect()
+}
+
+impl TagQuery {
+    pub fn new(tag: &str) -> Self {
+        TagQuery {
+            tag: tag.trim_start_matches('#').to_lowercase(),
+            ..Default::default()
+        }
+    }
+
+    /// Query string of `/api/v1/timelines/tag/:hashtag`
+    pub fn params(&self) -> Vec<(&'static str, &str)> {
+        let lists = [
+            ("any[]", &self.any),
+            ("all[]", &self.all),
+            ("none[]", &self.none),
+        ];
+        let mut params: Vec<_> = lists
+            .into_iter()
+            .flat_map(|(key, tags)| tags.iter().map(move |t| (key, t.as_str())))
+            .collect();
+        if self.local {
+            params.push(("local", "true"));
+        }
+        params
+    }
+}
+
+impl FromStr for TagQuery {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let tag = words
+            .next()
+            .map(|t| t.trim_start_matches('#'))
+            .filter(|t| is_hashtag(t))
+            .ok_or(anyhow!("Start with the hashtag"))?;
+        let mut query = TagQuery::new(tag);
+        for word in words {
+            match word.split_once(':') {
+                Some(("any", list)) => query.any.extend(hashtags(list)?),
+                Some(("all", list)) => query.all.extend(hashtags(list)?),
+                Some(("none", list)) => query.none.extend(hashtags(list)?),
+                None if word == "local" => query.local = true,
+                _ => return Err(anyhow!("Unknown option: {}", word)),
+            }
+        }
+        Ok(query)
+    }
+}
+
+impl Display for TagQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.tag)?;
+        for (key, tags) in [("any", &self.any), ("all", &self.all), ("none", &self.none)] {
+            if !tags.is_empty() {
+                write!(f, " {}:{}", key, tags.join(","))?;
+            }
+        }
+        if self.local {
+            write!(f, " local")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TagQuery;
+
+    #[test]
+    fn query_round_trip() {
+        let query: TagQuery = "#Rust any:zig,#GoLang none:nsfw local".parse().unwrap();
+        assert_eq!(query.tag, "rust");
+        assert_eq!(query.any, ["zig", "golang"]);
+        assert_eq!(
+            query.params(),
+            [
+                ("any[]", "zig"),
+                ("any[]", "golang"),
+                ("none[]", "nsfw"),
+                ("local", "true")
+            ]
+        );
+        assert_eq!(query.to_string().parse::<TagQuery>().unwrap(), query);
+        assert!("#rust some:zig".parse::<TagQuery>().is_err());
+        assert!("#c++".parse::<TagQuery>().is_err());
+    }
+}
@@ -1,18 +1,33 @@
-use anyhow::anyhow;
-use serde::Deserialize;
+use std::borrow::Cow;
 
-use super::account::Account;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
 
-#[derive(Deserialize, Debug)]
+use super::{
+    account::Account,
+    emoji::{CustomEmoji, EmojiReaction},
+    html::{attribute, strip_html},
+    lenient::{lenient_vec, null_default},
+};
+
+#[derive(Deserialize, Debug, Clone)]
 pub struct MediaAttatchmentRaw {
     id: String,
     #[serde(rename = "type")]
     type_: String,
-    url: String,
-    preview_url: String,
+    /// `null` for remote media the server didn't fetch
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    remote_url: Option<String>,
+    #[serde(default)]
+    preview_url: Option<String>,
+    #[serde(default)]
     description: Option<String>,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+#[serde(from = "MediaAttatchmentRaw")]
 pub enum MediaAttatchment {
     Image {
         id: String,
@@ -38,95 +53,649 @@ pub enum MediaAttatchment {
         preview_url: String,
         description: Option<String>,
     },
+    /// Servers send "unknown" for remote media they couldn't fetch
+    Unknown {
+        id: String,
+        url: String,
+        preview_url: String,
+        description: Option<String>,
+    },
 }
 
-impl TryFrom<MediaAttatchmentRaw> for MediaAttatchment {
-    type Error = anyhow::Error;
-
-    fn try_from(value: MediaAttatchmentRaw) -> Result<Self, Self::Error> {
+impl From<MediaAttatchmentRaw> for MediaAttatchment {
+    fn from(value: MediaAttatchmentRaw) -> Self {
+        let url = value.url.or(value.remote_url).unwrap_or_default();
+        let preview_url = value.preview_url.unwrap_or(url.clone());
         match value.type_.as_str() {
-            "image" => Ok(MediaAttatchment::Image {
+            "image" => MediaAttatchment::Image {
                 id: value.id,
-                url: value.url,
-                preview_url: value.preview_url,
+                url,
+                preview_url,
                 description: value.description,
-            }),
-            "video" => Ok(MediaAttatchment::Video {
+            },
+            "video" => MediaAttatchment::Video {
                 id: value.id,
-                url: value.url,
-                preview_url: value.preview_url,
+                url,
+                preview_url,
                 description: value.description,
-            }),
-            "gifv" => Ok(MediaAttatchment::Gifv {
+            },
+            "gifv" => MediaAttatchment::Gifv {
                 id: value.id,
-                url: value.url,
-                preview_url: value.preview_url,
+                url,
+                preview_url,
                 description: value.description,
-            }),
-            "audio" => Ok(MediaAttatchment::Audio {
+            },
+            "audio" => MediaAttatchment::Audio {
                 id: value.id,
-                url: value.url,
-                preview_url: value.preview_url,
+                url,
+                preview_url,
                 description: value.description,
-            }),
-            _ => Err(anyhow!("Invalid type: {}", value.type_)),
+            },
+            _ => MediaAttatchment::Unknown {
+                id: value.id,
+                url,
+                preview_url,
+                description: value.description,
+            },
+        }
+    }
+}
+
+impl MediaAttatchment {
+    pub fn id(&self) -> &str {
+        match self {
+            MediaAttatchment::Image { id, .. }
+            | MediaAttatchment::Video { id, .. }
+            | MediaAttatchment::Gifv { id, .. }
+            | MediaAttatchment::Audio { id, .. }
+            | MediaAttatchment::Unknown { id, .. } => id,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        match self {
+            MediaAttatchment::Image { url, .. }
+            | MediaAttatchment::Video { url, .. }
+            | MediaAttatchment::Gifv { url, .. }
+            | MediaAttatchment::Audio { url, .. }
+            | MediaAttatchment::Unknown { url, .. } => url,
+        }
+    }
+
+    pub fn preview_url(&self) -> &str {
+        match self {
+            MediaAttatchment::Image { preview_url, .. }
+            | MediaAttatchment::Video { preview_url, .. }
+            | MediaAttatchment::Gifv { preview_url, .. }
+            | MediaAttatchment::Audio { preview_url, .. }
+            | MediaAttatchment::Unknown { preview_url, .. } => preview_url,
+        }
+    }
+
+    /// The alt text, `None` when missing or blank
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            MediaAttatchment::Image { description, .. }
+            | MediaAttatchment::Video { description, .. }
+            | MediaAttatchment::Gifv { description, .. }
+            | MediaAttatchment::Audio { description, .. }
+            | MediaAttatchment::Unknown { description, .. } => {
+                description.as_deref().filter(|d| !d.trim().is_empty())
+            }
+        }
+    }
+
+    pub fn icon(&self) -> char {
+        match self {
+            MediaAttatchment::Image { .. } => '\u{1f5bc}',
+            MediaAttatchment::Video { .. } => '\u{1f39e}',
+            MediaAttatchment::Gifv { .. } => '\u{1f501}',
+            MediaAttatchment::Audio { .. } => '\u{1f50a}',
+            MediaAttatchment::Unknown { .. } => '\u{1f4ce}',
         }
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct TweetMention {
     id: String,
-    acct: String,
+    pub acct: String,
+    #[serde(default)]
     url: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct TweetTag {
-    name: String,
+    pub name: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct PollOption {
     title: String,
-    votes_count: u32,
+    /// `null` while the results are hidden
+    #[serde(default)]
+    votes_count: Option<u32>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Poll {
     id: String,
-    expires_at: String,
-    expired: bool,
+    /// `null` for polls that never end
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub expired: bool,
+    #[serde(default)]
     multiple: bool,
-    votes_count: u32,
+    #[serde(default, deserialize_with = "null_default")]
+    pub votes_count: u32,
+    #[serde(default)]
     voters_count: Option<u32>,
+    #[serde(default, deserialize_with = "lenient_vec")]
+    options: Vec<PollOption>,
+}
+
+/// Preview of a link in a post
+#[derive(Deserialize, Debug, Clone)]
+pub struct Card {
+    pub url: String,
+    #[serde(default, deserialize_with = "null_default")]
+    pub title: String,
+    #[serde(default, deserialize_with = "null_default")]
+    pub description: String,
+    #[serde(default, deserialize_with = "null_default")]
+    pub provider_name: String,
+}
+
+/// The posts around a status in its thread
+#[derive(Deserialize, Debug, Clone)]
+pub struct Context {
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub ancestors: Vec<Tweet>,
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub descendants: Vec<Tweet>,
 }
 
-#[derive(Deserialize, Debug)]
+/// What the author wrote, to edit the post starting from it
+#[derive(Deserialize, Debug, Clone)]
+pub struct TweetSource {
+    pub id: String,
+    pub text: String,
+    pub spoiler_text: String,
+    /// Mime type of `text`, only Pleroma sends it
+    #[serde(default)]
+    pub content_type: Option<String>,
+}
+
+/// A past version of an edited post, the last one being the current
+#[derive(Deserialize, Debug, Clone)]
+pub struct TweetRevision {
+    pub content: String,
+    #[serde(default, deserialize_with = "null_default")]
+    pub spoiler_text: String,
+    #[serde(default)]
+    pub sensitive: bool,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub media_attachments: Vec<MediaAttatchment>,
+}
+
+/// The content in other formats than HTML
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PleromaContent {
+    #[serde(rename = "text/plain")]
+    pub plain: Option<String>,
+}
+
+/// Fields only Pleroma and Akkoma send
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct TweetPleroma {
+    #[serde(deserialize_with = "lenient_vec")]
+    pub emoji_reactions: Vec<EmojiReaction>,
+    /// Posted on this instance
+    pub local: bool,
+    pub conversation_id: Option<u64>,
+    pub in_reply_to_account_acct: Option<String>,
+    pub content: Option<PleromaContent>,
+    /// When the post deletes itself
+    pub expires_at: Option<String>,
+    pub thread_muted: Option<bool>,
+    /// Whether we can see the post this replies to
+    pub parent_visible: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
 pub struct Tweet {
     pub id: String,
-    pub created_at: String,
-    pub in_response_to_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub in_reply_to_id: Option<String>,
+    #[serde(default)]
     pub in_reply_to_account_id: Option<String>,
+    #[serde(default, deserialize_with = "null_default")]
     pub sensitive: bool,
+    #[serde(default, deserialize_with = "null_default")]
     pub spoiler_text: String,
-    pub visibility: String,
+    /// Unknown rather than public when missing or `null`
+    #[serde(default = "unknown_visibility", deserialize_with = "visibility")]
+    pub visibility: Visibility,
+    #[serde(default, deserialize_with = "null_default")]
     pub uri: String,
+    #[serde(default, deserialize_with = "null_default")]
     pub replies_count: u32,
+    #[serde(default, deserialize_with = "null_default")]
     pub reblogs_count: u32,
+    #[serde(default, deserialize_with = "null_default")]
     pub favourites_count: u32,
+    /// The following are missing when not logged in
+    #[serde(default, deserialize_with = "null_default")]
     pub favourited: bool,
+    #[serde(default, deserialize_with = "null_default")]
     pub reblogged: bool,
+    #[serde(default, deserialize_with = "null_default")]
     pub muted: bool,
+    #[serde(default, deserialize_with = "null_default")]
+    pub bookmarked: bool,
+    #[serde(default, deserialize_with = "null_default")]
     pub content: String,
+    /// Missing on servers without post editing
+    #[serde(default)]
+    pub edited_at: Option<DateTime<Utc>>,
+    /// Source of the post, only sent back when deleting it
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
     pub reblog: Option<Box<Tweet>>,
     pub account: Account,
-    pub media_attachments: Vec<MediaAttatchmentRaw>,
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub media_attachments: Vec<MediaAttatchment>,
+    #[serde(default, deserialize_with = "lenient_vec")]
     pub mentions: Vec<TweetMention>,
+    #[serde(default, deserialize_with = "lenient_vec")]
     pub tags: Vec<TweetTag>,
+    #[serde(default)]
     pub poll: Option<Poll>,
+    #[serde(default)]
+    pub card: Option<Card>,
+    /// Custom emoji used in the content and the CW
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub emojis: Vec<CustomEmoji>,
+    #[serde(default, deserialize_with = "null_default")]
+    pub pleroma: TweetPleroma,
 }
 
 impl Tweet {
-    // TODO
+    /// The content as plain text, as the server sends it or else with the
+    /// HTML tags stripped
+    pub fn plain_content(&self) -> Cow<'_, str> {
+        match self
+            .pleroma
+            .content
+            .as_ref()
+            .and_then(|c| c.plain.as_deref())
+        {
+            Some(plain) => Cow::Borrowed(plain),
+            None => Cow::Owned(strip_html(&self.content).trim_end().to_string()),
+        }
+    }
+
+    /// Links in the content, without mentions and hashtags, then the
+    /// card's if the server resolved it to another address
+    pub fn links(&self) -> Vec<String> {
+        let mut links: Vec<String> = Vec::new();
+        let mut rest = self.content.as_str();
+        while let Some(start) = rest.find("<a ") {
+            rest = &rest[start..];
+            let end = match rest.find('>') {
+                Some(end) => end,
+                None => break,
+            };
+            let tag = &rest[..end];
+            rest = &rest[end..];
+
+            let class = attribute(tag, "class").unwrap_or_default();
+            if class.contains("mention") || class.contains("hashtag") {
+                continue;
+            }
+            if let Some(href) = attribute(tag, "href")
+                && !links.contains(&href)
+            {
+                links.push(href);
+            }
+        }
+        if let Some(card) = &self.card
+            && !links.contains(&card.url)
+        {
+            links.push(card.url.clone());
+        }
+        links
+    }
+}
+
+/// Who can see a post
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    Public,
+    /// Public but kept out of the public timelines
+    Unlisted,
+    /// Followers only
+    Private,
+    /// Mentioned accounts only
+    Direct,
+    /// Pleroma, only accounts of the same instance
+    Local,
+    /// Pleroma, members of one of the author's lists
+    List,
+    /// Whatever else a server comes up with
+    #[serde(other)]
+    Unknown,
+}
+
+fn unknown_visibility() -> Visibility {
+    Visibility::Unknown
+}
+
+/// Like `null_default`, but a post we can't tell the audience of isn't
+/// treated as public
+fn visibility<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Visibility, D::Error> {
+    Ok(Option::<Visibility>::deserialize(deserializer)?.unwrap_or(Visibility::Unknown))
+}
+
+impl Visibility {
+    /// What can be picked when composing, lists need a list id
+    pub const COMPOSE: [Visibility; 5] = [
+        Visibility::Public,
+        Visibility::Unlisted,
+        Visibility::Private,
+        Visibility::Direct,
+        Visibility::Local,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Unlisted => "unlisted",
+            Visibility::Private => "followers only",
+            Visibility::Direct => "direct",
+            Visibility::Local => "local",
+            Visibility::List => "list",
+            Visibility::Unknown => "unknown",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Visibility::Public => "\u{1f310}",
+            Visibility::Unlisted => "\u{1f513}",
+            Visibility::Private => "\u{1f512}",
+            Visibility::Direct => "\u{2709}",
+            Visibility::Local => "\u{2302}",
+            Visibility::List => "\u{2630}",
+            Visibility::Unknown => "?",
+        }
+    }
+
+    /// The closest visibility a new post can have, for replies and
+    /// redrafts. Errs on the side of fewer people seeing it.
+    pub fn for_compose(self) -> Self {
+        match self {
+            Visibility::List | Visibility::Unknown => Visibility::Private,
+            visibility => visibility,
+        }
+    }
+}
+
+/// Markup of the text of a new status
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum ContentType {
+    #[default]
+    #[serde(rename = "text/plain")]
+    PlainText,
+    #[serde(rename = "text/markdown")]
+    Markdown,
+    #[serde(rename = "text/html")]
+    Html,
+    #[serde(rename = "text/bbcode")]
+    BBCode,
+}
+
+impl ContentType {
+    pub fn from_mime(mime: &str) -> Option<Self> {
+        match mime {
+            "text/plain" => Some(ContentType::PlainText),
+            "text/markdown" => Some(ContentType::Markdown),
+            "text/html" => Some(ContentType::Html),
+            "text/bbcode" => Some(ContentType::BBCode),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContentType::PlainText => "plain text",
+            ContentType::Markdown => "Markdown",
+            ContentType::Html => "HTML",
+            ContentType::BBCode => "BBCode",
+        }
+    }
+}
+
+/// Parameters of a status about to be published
+#[derive(Serialize, Debug, Clone)]
+pub struct NewTweet {
+    status: String,
+    content_type: ContentType,
+    source: String,
+    visibility: Visibility,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    spoiler_text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    in_reply_to_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    media_ids: Vec<String>,
+    /// ISO 8601 time to publish at, at least 5 minutes from now
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scheduled_at: Option<String>,
+}
+
+impl NewTweet {
+    pub fn new(text: &str) -> Self {
+        NewTweet {
+            status: text.to_string(),
+            content_type: ContentType::PlainText,
+            source: "plerustax".to_string(),
+            visibility: Visibility::Public,
+            spoiler_text: String::new(),
+            in_reply_to_id: None,
+            media_ids: Vec::new(),
+            scheduled_at: None,
+        }
+    }
+
+    pub fn visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    pub fn content_type(mut self, content_type: ContentType) -> Self {
+        self.content_type = content_type;
+        self
+    }
+
+    pub fn spoiler_text(mut self, spoiler_text: &str) -> Self {
+        self.spoiler_text = spoiler_text.to_string();
+        self
+    }
+
+    pub fn in_reply_to_id(mut self, id: Option<String>) -> Self {
+        self.in_reply_to_id = id;
+        self
+    }
+
+    pub fn media_ids(mut self, media_ids: Vec<String>) -> Self {
+        self.media_ids = media_ids;
+        self
+    }
+
+    pub fn scheduled_at(mut self, scheduled_at: Option<String>) -> Self {
+        self.scheduled_at = scheduled_at;
+        self
+    }
+
+    /// Attachments already on the server, when editing
+    pub fn kept_media_ids(&self) -> &[String] {
+        &self.media_ids
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use serde::de::DeserializeOwned;
+    use serde_json::Value;
+
+    use super::{MediaAttatchment, MediaAttatchmentRaw, Tweet, Visibility};
+    use crate::pleroma::lenient::skip_malformed;
+
+    /// A post, or any JSON, from `tests/fixtures`
+    pub fn fixture<T: DeserializeOwned>(name: &str) -> T {
+        let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn mastodon_status() {
+        let tweet: Tweet = fixture("mastodon_status.json");
+        assert!(tweet.bookmarked && tweet.reblogged);
+        assert_eq!(
+            tweet.plain_content(),
+            "Release day! Notes at https://example.org/notes"
+        );
+        assert!(tweet.edited_at > Some(tweet.created_at));
+        assert_eq!(tweet.account.fields.len(), 1);
+        assert_eq!(tweet.card.as_ref().unwrap().title, "Release notes");
+        assert_eq!(
+            tweet.links(),
+            ["https://example.org/notes", "https://example.org/notes/4.2"]
+        );
+        let poll = tweet.poll.unwrap();
+        assert_eq!(poll.options.len(), 2);
+        assert_eq!(
+            poll.expires_at.unwrap().to_rfc3339(),
+            "2023-10-26T14:32:11+00:00"
+        );
+        assert!(!tweet.pleroma.local);
+    }
+
+    #[test]
+    fn links_without_mentions_and_hashtags() {
+        let mut tweet: Tweet = fixture("mastodon_status.json");
+        tweet.card = None;
+        tweet.content = concat!(
+            r#"<p><span class="h-card"><a href="https://a.example/@bob" class="u-url mention">@bob</a></span> "#,
+            r#"<a href="https://a.example/tags/rust" class="mention hashtag" rel="tag">#rust</a> "#,
+            r#"<a data-href="https://nope.example" href='https://x.example/?a=1&amp;b=2' target=_blank>x</a> "#,
+            r#"<a rel=nofollow href=https://y.example/&#x27;q&#39;>y</a> "#,
+            r#"<a href="https://x.example/?a=1&amp;b=2">again</a> <a name="anchor">no link</a></p>"#,
+        )
+        .to_string();
+        assert_eq!(
+            tweet.links(),
+            ["https://x.example/?a=1&b=2", "https://y.example/'q'"]
+        );
+    }
+
+    #[test]
+    fn pleroma_status() {
+        let tweet: Tweet = fixture("pleroma_status.json");
+        assert_eq!(tweet.plain_content(), "@bob agreed\nsee you there");
+        assert!(tweet.pleroma.local);
+        assert_eq!(tweet.pleroma.conversation_id, Some(483921));
+        assert_eq!(
+            tweet.pleroma.in_reply_to_account_acct.as_deref(),
+            Some("bob")
+        );
+        assert_eq!(tweet.pleroma.emoji_reactions.len(), 2);
+        assert!(tweet.account.is_admin());
+    }
+
+    #[test]
+    fn akkoma_status() {
+        let tweet: Tweet = fixture("akkoma_status.json");
+        assert!(tweet.account.is_moderator() && tweet.account.locked);
+        assert_eq!(tweet.visibility, Visibility::Local);
+        assert_eq!(tweet.account.statuses_count, 0);
+        assert_eq!(tweet.pleroma.parent_visible, Some(false));
+        assert!(tweet.pleroma.expires_at.is_some());
+        let relationship = tweet.account.pleroma.unwrap().relationship.unwrap();
+        assert!(relationship.followed_by);
+    }
+
+    #[test]
+    fn gotosocial_status() {
+        let tweet: Tweet = fixture("gotosocial_status.json");
+        assert!(tweet.account.fields.is_empty());
+        let poll = tweet.poll.unwrap();
+        assert_eq!(poll.expires_at, None);
+        assert_eq!(poll.options[0].votes_count, None);
+        // No preview for audio, falls back to the file itself
+        let audio = &tweet.media_attachments[0];
+        assert!(matches!(audio, MediaAttatchment::Audio { .. }));
+        assert_eq!(audio.preview_url(), audio.url());
+    }
+
+    #[test]
+    fn malformed_status_skipped() {
+        let items: Vec<Value> = fixture("mastodon_2_timeline.json");
+        let tweets: Vec<Tweet> = skip_malformed(items);
+        assert_eq!(tweets.len(), 2);
+
+        // Remote media the server couldn't fetch
+        assert!(matches!(
+            tweets[0].media_attachments[0],
+            MediaAttatchment::Unknown { .. }
+        ));
+        assert_eq!(
+            tweets[0].media_attachments[0].url(),
+            "https://elsewhere.example/files/clip.webm"
+        );
+
+        // Logged out, `null`s and an attachment without id
+        assert!(!tweets[1].sensitive && !tweets[1].favourited);
+        assert_eq!(tweets[1].spoiler_text, "");
+        assert!(!tweets[1].account.bot);
+        assert_eq!(tweets[1].media_attachments.len(), 1);
+    }
+
+    #[test]
+    fn unknown_attachment_type() {
+        let raw = MediaAttatchmentRaw {
+            id: "1".to_string(),
+            type_: "unknown".to_string(),
+            url: Some("https://example.com/a".to_string()),
+            remote_url: None,
+            preview_url: Some("https://example.com/a".to_string()),
+            description: Some(" ".to_string()),
+        };
+        let media = MediaAttatchment::from(raw);
+        assert!(matches!(media, MediaAttatchment::Unknown { .. }));
+        assert_eq!(media.description(), None);
+    }
+
+    #[test]
+    fn unknown_visibility() {
+        let visibility: Visibility = serde_json::from_str("\"mutuals_only\"").unwrap();
+        assert_eq!(visibility, Visibility::Unknown);
+        assert_eq!(visibility.for_compose(), Visibility::Private);
+
+        let mut status: Value = fixture("mastodon_status.json");
+        status["visibility"] = Value::Null;
+        let tweet: Tweet = serde_json::from_value(status.clone()).unwrap();
+        assert_eq!(tweet.visibility, Visibility::Unknown);
+        status.as_object_mut().unwrap().remove("visibility");
+        let tweet: Tweet = serde_json::from_value(status).unwrap();
+        assert_eq!(tweet.visibility, Visibility::Unknown);
+    }
 }
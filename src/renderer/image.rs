@@ -0,0 +1,214 @@
+use std::{collections::BTreeMap, env::var, fmt::Write};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use crossterm::terminal::window_size;
+use image::{
+    RgbaImage,
+    imageops::{FilterType, resize},
+};
+
+/// Used when the terminal doesn't report its size in pixels
+const DEFAULT_CELL_SIZE: (u32, u32) = (10, 20);
+/// Kitty refuses escape payloads bigger than this
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// How images get drawn on the terminal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GraphicsProtocol {
+    Kitty,
+    Sixel,
+    /// Two pixels per cell using `▀` with different fg and bg colours
+    HalfBlock,
+}
+
+impl GraphicsProtocol {
+    /// Guesses the protocol from the environment. Querying the terminal
+    /// would race with the input reader for its answer.
+    pub fn detect() -> Self {
+        let term = var("TERM").unwrap_or_default();
+        let term_program = var("TERM_PROGRAM").unwrap_or_default();
+        if var("KITTY_WINDOW_ID").is_ok()
+            || term.contains("kitty")
+            || term.contains("ghostty")
+            || term_program == "WezTerm"
+            || term_program == "ghostty"
+        {
+            GraphicsProtocol::Kitty
+        } else if term.contains("sixel")
+            || term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || term.contains("contour")
+        {
+            GraphicsProtocol::Sixel
+        } else {
+            GraphicsProtocol::HalfBlock
+        }
+    }
+}
+
+/// An image ready to be written to a region of cells
+pub enum EncodedImage {
+    /// Escape sequence to print from the top left cell of the region.
+    /// The rest of the region must be left untouched.
+    Escape(String),
+    /// One pixel per half cell, top half in even rows
+    HalfBlock(RgbaImage),
+}
+
+/// Size of a cell in pixels
+pub fn cell_size() -> (u32, u32) {
+    match window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            (size.width / size.columns) as u32,
+            (size.height / size.rows) as u32,
+        ),
+        _ => DEFAULT_CELL_SIZE,
+    }
+}
+
+/// Number of columns needed to show the image `rows` cells tall
+/// without stretching it.
+pub fn columns_for(image: &RgbaImage, rows: u16) -> u16 {
+    let (cell_width, cell_height) = cell_size();
+    let height = rows as u32 * cell_height;
+    let width = image.width() * height / image.height().max(1);
+    (width / cell_width).max(1) as u16
+}
+
+/// `id` lets protocols that keep images around replace the previous
+/// placement of the same image instead of stacking a new one.
+pub fn encode(
+    image: &RgbaImage,
+    protocol: GraphicsProtocol,
+    cols: u16,
+    rows: u16,
+    id: u32,
+) -> EncodedImage {
+    match protocol {
+        GraphicsProtocol::Kitty => EncodedImage::Escape(kitty(image, cols, rows, id)),
+        GraphicsProtocol::Sixel => {
+            let (cell_width, cell_height) = cell_size();
+            let resized = resize(
+                image,
+                cols as u32 * cell_width,
+                rows as u32 * cell_height,
+                FilterType::Triangle,
+            );
+            EncodedImage::Escape(sixel(&resized))
+        }
+        GraphicsProtocol::HalfBlock => EncodedImage::HalfBlock(resize(
+            image,
+            cols as u32,
+            rows as u32 * 2,
+            FilterType::Triangle,
+        )),
+    }
+}
+
+/// Transmits raw RGBA data and lets the terminal scale it to the cells
+fn kitty(image: &RgbaImage, cols: u16, rows: u16, id: u32) -> String {
+    let data = STANDARD.encode(image.as_raw());
+    let chunks = data.as_bytes().chunks(KITTY_CHUNK_SIZE).collect::<Vec<_>>();
+    let mut res = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = if i + 1 < chunks.len() { 1 } else { 0 };
+        // Chunks are slices of a base64 string, so they are valid UTF-8
+        let chunk = std::str::from_utf8(chunk).unwrap();
+        if i == 0 {
+            let _ = write!(
+                res,
+                "\x1b_Ga=T,f=32,s={},v={},c={},r={},i={},p=1,q=2,C=1,m={};{}\x1b\\",
+                image.width(),
+                image.height(),
+                cols,
+                rows,
+                id,
+                more,
+                chunk
+            );
+        } else {
+            let _ = write!(res, "\x1b_Gm={};{}\x1b\\", more, chunk);
+        }
+    }
+    res
+}
+
+/// Index in the 6x6x6 colour cube, `None` for transparent pixels
+fn sixel_color(pixel: &image::Rgba<u8>) -> Option<u16> {
+    if pixel[3] < 128 {
+        return None;
+    }
+    let level = |c: u8| (c as u16 * 5 + 127) / 255;
+    Some(level(pixel[0]) * 36 + level(pixel[1]) * 6 + level(pixel[2]))
+}
+
+fn sixel(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+    let mut res = format!("\x1bP0;1;0q\"1;1;{};{}", width, height);
+
+    let mut bands = Vec::new();
+    let mut used = [false; 216];
+    for top in (0..height).step_by(6) {
+        // Colour → sixel bits for each column of the band
+        let mut band: BTreeMap<u16, Vec<u8>> = BTreeMap::new();
+        for dy in 0..6.min(height - top) {
+            for x in 0..width {
+                if let Some(color) = sixel_color(image.get_pixel(x, top + dy)) {
+                    used[color as usize] = true;
+                    band.entry(color).or_insert_with(|| vec![0; width as usize])[x as usize] |=
+                        1 << dy;
+                }
+            }
+        }
+        bands.push(band);
+    }
+
+    for (color, _) in used.iter().enumerate().filter(|(_, u)| **u) {
+        let percent = |level: usize| level * 20;
+        let _ = write!(
+            res,
+            "#{};2;{};{};{}",
+            color,
+            percent(color / 36),
+            percent(color / 6 % 6),
+            percent(color % 6)
+        );
+    }
+
+    for band in bands {
+        for (color, bits) in band {
+            let _ = write!(res, "#{}", color);
+            let mut i = 0;
+            while i < bits.len() {
+                let run = bits[i..].iter().take_while(|b| **b == bits[i]).count();
+                let c = (63 + bits[i]) as char;
+                if run > 3 {
+                    let _ = write!(res, "!{}{}", run, c);
+                } else {
+                    (0..run).for_each(|_| res.push(c));
+                }
+                i += run;
+            }
+            res.push('$');
+        }
+        res.push('-');
+    }
+    res.push_str("\x1b\\");
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use image::{Rgba, RgbaImage};
+
+    use super::sixel;
+
+    #[test]
+    fn sixel_run_length() {
+        let image = RgbaImage::from_pixel(8, 6, Rgba([255, 0, 0, 255]));
+        assert_eq!(
+            sixel(&image),
+            "\x1bP0;1;0q\"1;1;8;6#180;2;100;0;0#180!8~$-\x1b\\"
+        );
+    }
+}
@@ -8,7 +8,7 @@ use crossterm::{
     ExecutableCommand,
     cursor::{MoveTo, position},
     style::{Color, Print, Stylize},
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
 };
 
 use crate::renderer::utils::Drawable;
@@ -161,6 +161,21 @@ impl Terminal {
             .join("\n")
     }
 
+    /// Gives the terminal back to the shell, for programs that need it.
+    /// Call `resume` once they exit.
+    pub fn suspend(&mut self) -> Result<()> {
+        crossterm::terminal::disable_raw_mode()?;
+        self.output.execute(LeaveAlternateScreen)?;
+        Ok(())
+    }
+
+    pub fn resume(&mut self) -> Result<()> {
+        self.output.execute(EnterAlternateScreen)?;
+        crossterm::terminal::enable_raw_mode()?;
+        self.output.execute(Clear(ClearType::All))?;
+        Ok(())
+    }
+
     /// Method used to draw the widget on screen
     pub fn draw<T>(&mut self, callback: T) -> Result<()>
     where